@@ -1,4 +1,5 @@
-use rip::parser;
+use rip_rs::message::ParsedMessage;
+use rip_rs::parser;
 
 fn main() {
     let result = parser::parse(
@@ -8,29 +9,25 @@ fn main() {
         .as_slice(),
     );
 
-    let packet = match result.unwrap() {
-        parser::ParsedPacket::V1(_) => {
+    let message = match result.unwrap() {
+        ParsedMessage::V1(_) => {
             panic!("the packet version must not be 1 because the second byte is 2");
         }
-        parser::ParsedPacket::V2(p) => p,
+        ParsedMessage::V2(m) => m,
     };
 
-    println!("{:?}", packet);
+    println!("{:?}", message);
     // =>
-    //   Packet {
-    //     header: Header {
-    //       command: Response,
-    //       version: Version2
-    //     },
-    //     entries: [
+    //   Response(
+    //     [
     //       Entry {
     //         address_family_identifier: IP,
-    //         route_tag: 258,
+    //         route_tag: RouteTag(258),
     //         ip_address: 192.0.2.100,
     //         subnet_mask: 255.255.255.0,
     //         next_hop: 192.0.2.111,
-    //         metric: 67305985
+    //         metric: Metric(67305985)
     //       }
     //     ]
-    //   }
+    //   )
 }
@@ -1,7 +1,9 @@
-use rip::header::Header;
-use rip::packet::Packet;
-use rip::serializer::serialize_v2_packet;
-use rip::{address_family, command, v2, version};
+use rip_rs::header::Header;
+use rip_rs::metric::Metric;
+use rip_rs::packet::Packet;
+use rip_rs::route_tag::RouteTag;
+use rip_rs::serializer::serialize_v2_packet;
+use rip_rs::{address_family, command, v2, version};
 use std::net::Ipv4Addr;
 
 fn main() {
@@ -9,11 +11,11 @@ fn main() {
         Header::new(command::Kind::Response, version::Version::Version2),
         vec![v2::Entry::new(
             address_family::Identifier::IP,
-            258,
+            RouteTag::new(258),
             Ipv4Addr::new(192, 0, 2, 100),
             Ipv4Addr::new(255, 255, 255, 0),
             Ipv4Addr::new(192, 0, 2, 111),
-            67305985,
+            Metric::from_raw(67305985),
         )],
     )
     .unwrap();
@@ -1,15 +1,15 @@
 use crate::byte_reader;
 use crate::parser::ParseError;
-use crate::parser::Parsed;
-use crate::serializer::{Serializable, SerializeError};
-use SerializeError::UnknownAddressFamilyIdentifier;
+use crate::parser::{Parsable, Parsed};
+use crate::serializer::{write_slice, Serializable, SerializeError};
 
 #[derive(PartialEq, Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Identifier {
     Unspecified,           // RFC1058
     IP,                    // RFC1058
     AuthenticationPresent, // RFC1388
-    Unknown,
+    Unknown(u16),
 }
 
 impl Identifier {
@@ -18,57 +18,60 @@ impl Identifier {
             0 => Identifier::Unspecified,
             2 => Identifier::IP,
             65535 => Identifier::AuthenticationPresent,
-            _ => Identifier::Unknown,
+            _ => Identifier::Unknown(value),
         }
     }
 
-    pub fn to_u16(&self) -> Option<u16> {
+    pub fn to_u16(&self) -> u16 {
         match self {
-            Identifier::Unspecified => Some(0),
-            Identifier::IP => Some(2),
-            Identifier::AuthenticationPresent => Some(65535),
-            Identifier::Unknown => None,
+            Identifier::Unspecified => 0,
+            Identifier::IP => 2,
+            Identifier::AuthenticationPresent => 65535,
+            Identifier::Unknown(value) => *value,
         }
     }
+}
 
-    pub(crate) fn parse(cursor: usize, bytes: &[u8]) -> Result<Parsed<Identifier>, ParseError> {
-        let (address_family_identifier_first_byte, cursor) = byte_reader::read(cursor, bytes)?;
-        let (address_family_identifier_second_byte, cursor) = byte_reader::read(cursor, bytes)?;
+/// parse_any parses the address family identifier as well as [`Identifier::parse`],
+/// but it returns the unknown identifier as [`Identifier::Unknown`] instead of an error.
+pub(crate) fn parse_any(cursor: usize, bytes: &[u8]) -> Result<Parsed<Identifier>, ParseError> {
+    let (address_family_identifier_first_byte, cursor) = byte_reader::read(cursor, bytes)?;
+    let (address_family_identifier_second_byte, cursor) = byte_reader::read(cursor, bytes)?;
 
-        let address_family_identifier_value = ((address_family_identifier_first_byte as u16) << 8)
-            + address_family_identifier_second_byte as u16;
-        let address_family_identifier = match Identifier::from_u16(address_family_identifier_value)
-        {
-            Identifier::Unknown => {
-                return Err(ParseError::UnknownAddressFamilyIdentifier(
-                    address_family_identifier_value,
-                    cursor - 1,
-                ))
-            }
-            _identifier => _identifier,
-        };
+    let address_family_identifier_value = ((address_family_identifier_first_byte as u16) << 8)
+        + address_family_identifier_second_byte as u16;
+    Ok((
+        Identifier::from_u16(address_family_identifier_value),
+        cursor,
+    ))
+}
 
-        Ok((address_family_identifier, cursor))
+impl Parsable for Identifier {
+    fn parse(cursor: usize, bytes: &[u8]) -> Result<Parsed<Identifier>, ParseError> {
+        match parse_any(cursor, bytes)? {
+            (Identifier::Unknown(value), _) => {
+                Err(ParseError::UnknownAddressFamilyIdentifier(value, cursor))
+            }
+            parsed => Ok(parsed),
+        }
     }
 }
 
 impl Serializable for Identifier {
-    fn to_bytes(&self) -> Result<Vec<u8>, SerializeError> {
-        let v = match self.to_u16() {
-            Some(v) => v,
-            None => {
-                return Err(UnknownAddressFamilyIdentifier);
-            }
-        };
+    fn serialized_len(&self) -> usize {
+        2
+    }
 
-        Ok(vec![((v & 0xff00) >> 8) as u8, (v & 0x00ff) as u8])
+    fn write_bytes(&self, buf: &mut [u8]) -> Result<usize, SerializeError> {
+        let v = self.to_u16();
+        write_slice(&[((v & 0xff00) >> 8) as u8, (v & 0x00ff) as u8], buf)
     }
 }
 #[cfg(test)]
 mod tests {
     use crate::address_family::Identifier;
-    use crate::parser::ParseError;
-    use crate::serializer::{Serializable, SerializeError};
+    use crate::parser::{Parsable, ParseError};
+    use crate::serializer::Serializable;
 
     #[test]
     fn test_parse() {
@@ -85,7 +88,7 @@ mod tests {
         let result = Identifier::parse(0, vec![0x00, 0x01].as_slice());
         assert_eq!(
             result.unwrap_err(),
-            ParseError::UnknownAddressFamilyIdentifier(1, 1)
+            ParseError::UnknownAddressFamilyIdentifier(1, 0)
         );
     }
 
@@ -100,9 +103,18 @@ mod tests {
             Identifier::AuthenticationPresent.to_bytes().unwrap(),
             vec![0xff, 0xff]
         );
+        assert_eq!(Identifier::Unknown(1).to_bytes().unwrap(), vec![0x00, 0x01]);
         assert_eq!(
-            Identifier::Unknown.to_bytes().unwrap_err(),
-            SerializeError::UnknownAddressFamilyIdentifier
+            Identifier::Unknown(0x1234).to_bytes().unwrap(),
+            vec![0x12, 0x34]
         );
     }
+
+    #[test]
+    fn test_from_u16_and_to_u16_round_trip() {
+        for value in [0, 1, 2, 3, 0x1234, 65534, 65535] {
+            assert_eq!(Identifier::from_u16(value).to_u16(), value);
+        }
+        assert_eq!(Identifier::from_u16(1), Identifier::Unknown(1));
+    }
 }
@@ -0,0 +1,422 @@
+use rip_rs::diagnostic::{diagnose, hexdump};
+use rip_rs::message::ParsedMessage;
+use rip_rs::parser;
+use rip_rs::pcap;
+use rip_rs::prefix::Ipv4Prefix;
+use rip_rs::{command, version};
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read};
+use std::net::{IpAddr, SocketAddr};
+use std::process::ExitCode;
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const USAGE: &str = "\
+Usage: ripdump [OPTIONS] [HEX...]
+
+Decodes RIPv1/RIPv2 packets and prints them.
+
+Input (hex strings by default; they are read from stdin, one packet per line, when no HEX is given):
+  -r, --raw FILE          read a raw RIP packet from FILE (`-` for stdin)
+  -p, --pcap FILE         read the RIP packets from a pcap or pcapng FILE (`-` for stdin)
+
+Output:
+  -f, --format FORMAT     text (default), json or hexdump
+
+Filters:
+      --command COMMAND   print only the packets of the command, e.g. response
+      --version VERSION   print only the packets of the version, 1 or 2
+      --source ADDRESS    print only the packets from the address (pcap input only)
+      --prefix PREFIX     print only the packets that have a route within the prefix, e.g. 10.0.0.0/8
+
+  -h, --help              print this help
+";
+
+#[derive(PartialEq, Debug)]
+enum Input {
+    Hex(Vec<String>),
+    Raw(String),
+    Pcap(String),
+}
+
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+enum Format {
+    Text,
+    Json,
+    Hexdump,
+}
+
+#[derive(PartialEq, Debug, Default)]
+struct Filter {
+    command: Option<command::Kind>,
+    version: Option<version::Version>,
+    source: Option<IpAddr>,
+    prefix: Option<Ipv4Prefix>,
+}
+
+impl Filter {
+    fn matches(&self, record: &Record) -> bool {
+        let message = &record.message;
+        self.command.is_none_or(|c| c == message.get_command())
+            && self.version.is_none_or(|v| v == message.get_version())
+            && self
+                .source
+                .is_none_or(|s| record.source.map(|a| a.ip()) == Some(s))
+            && self.prefix.is_none_or(|p| {
+                message
+                    .entries()
+                    .any(|entry| p.contains(entry.destination()))
+            })
+    }
+}
+
+#[derive(PartialEq, Debug)]
+struct Options {
+    input: Input,
+    format: Format,
+    filter: Filter,
+}
+
+/// Record is a decoded message with the capture information if the input is pcap.
+struct Record {
+    frame_number: Option<u64>,
+    timestamp: Option<SystemTime>,
+    source: Option<SocketAddr>,
+    destination: Option<SocketAddr>,
+    bytes: Vec<u8>,
+    message: ParsedMessage,
+}
+
+fn main() -> ExitCode {
+    let options = match parse_args(std::env::args().skip(1)) {
+        Ok(Some(options)) => options,
+        Ok(None) => {
+            print!("{}", USAGE);
+            return ExitCode::SUCCESS;
+        }
+        Err(e) => {
+            eprintln!("ripdump: {}\n\n{}", e, USAGE);
+            return ExitCode::from(2);
+        }
+    };
+
+    match run(&options) {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        Err(e) => {
+            eprintln!("ripdump: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// parse_args parses the command line arguments; it returns None when the help is requested.
+fn parse_args<I: Iterator<Item = String>>(mut args: I) -> Result<Option<Options>, String> {
+    let mut hex = vec![];
+    let mut input = None;
+    let mut format = Format::Text;
+    let mut filter = Filter::default();
+
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| {
+            args.next()
+                .ok_or_else(|| format!("option {} requires a value", name))
+        };
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(None),
+            "-r" | "--raw" => input = Some(Input::Raw(value(&arg)?)),
+            "-p" | "--pcap" => input = Some(Input::Pcap(value(&arg)?)),
+            "-f" | "--format" => {
+                format = match value(&arg)?.as_str() {
+                    "text" => Format::Text,
+                    "json" => Format::Json,
+                    "hexdump" => Format::Hexdump,
+                    other => return Err(format!("unknown format \"{}\"", other)),
+                }
+            }
+            "--command" => {
+                let name = value(&arg)?;
+                filter.command = Some(
+                    command::Kind::from_str(&name)
+                        .map_err(|_| format!("unknown command \"{}\"", name))?,
+                );
+            }
+            "--version" => {
+                let name = value(&arg)?;
+                // the bare number (e.g. `2`) is accepted as well as the notation of the text format
+                let notation = if name.starts_with('v') {
+                    name.clone()
+                } else {
+                    format!("v{}", name)
+                };
+                filter.version = Some(
+                    version::Version::from_str(&notation)
+                        .map_err(|_| format!("unsupported version \"{}\"", name))?,
+                );
+            }
+            "--source" => {
+                let address = value(&arg)?;
+                filter.source = Some(
+                    IpAddr::from_str(&address)
+                        .map_err(|_| format!("invalid address \"{}\"", address))?,
+                );
+            }
+            "--prefix" => {
+                let prefix = value(&arg)?;
+                filter.prefix = Some(Ipv4Prefix::from_str(&prefix).map_err(|e| e.to_string())?);
+            }
+            _ if arg.starts_with('-') && arg.len() > 1 => {
+                return Err(format!("unknown option \"{}\"", arg))
+            }
+            _ => hex.push(arg),
+        }
+    }
+
+    let input = match input {
+        Some(_) if !hex.is_empty() => {
+            return Err("hex strings can't be given with a file input".to_string())
+        }
+        Some(input) => input,
+        None => Input::Hex(hex),
+    };
+    Ok(Some(Options {
+        input,
+        format,
+        filter,
+    }))
+}
+
+/// run prints the packets of the input; it returns false when any packet fails to be decoded.
+fn run(options: &Options) -> Result<bool, String> {
+    let mut ok = true;
+    let mut emit = |record: Record| {
+        if options.filter.matches(&record) {
+            print_record(&record, options.format);
+        }
+    };
+
+    match &options.input {
+        Input::Hex(hex) => {
+            let lines = if hex.is_empty() {
+                io::stdin()
+                    .lock()
+                    .lines()
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(|e| e.to_string())?
+            } else {
+                hex.clone()
+            };
+            for line in lines {
+                let line = line.trim();
+                if line.is_empty() || line.starts_with('#') {
+                    continue;
+                }
+                match decode_hex(line) {
+                    Ok(bytes) => ok &= decode_bytes(bytes, &mut emit),
+                    Err(e) => {
+                        eprintln!("ripdump: {}", e);
+                        ok = false;
+                    }
+                }
+            }
+        }
+        Input::Raw(path) => {
+            let mut bytes = vec![];
+            open(path)?
+                .read_to_end(&mut bytes)
+                .map_err(|e| format!("{}: {}", path, e))?;
+            ok &= decode_bytes(bytes, &mut emit);
+        }
+        Input::Pcap(path) => {
+            let reader = pcap::Reader::new(BufReader::new(open(path)?))
+                .map_err(|e| format!("{}: {}", path, e))?;
+            for capture in reader {
+                match capture {
+                    Ok(capture) => {
+                        let frame_number = capture.get_frame_number();
+                        let (timestamp, source, destination, message, payload) =
+                            capture.into_parts();
+                        emit(Record {
+                            frame_number: Some(frame_number),
+                            timestamp: Some(timestamp),
+                            source: Some(source),
+                            destination: Some(destination),
+                            bytes: payload,
+                            message,
+                        });
+                    }
+                    Err(e) => {
+                        eprintln!("ripdump: {}: {}", path, e);
+                        ok = false;
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(ok)
+}
+
+fn open(path: &str) -> Result<Box<dyn Read>, String> {
+    if path == "-" {
+        return Ok(Box::new(io::stdin()));
+    }
+    File::open(path)
+        .map(|file| Box::new(file) as Box<dyn Read>)
+        .map_err(|e| format!("{}: {}", path, e))
+}
+
+/// decode_bytes parses the message; the failure is reported with the annotated hexdump.
+fn decode_bytes<F: FnMut(Record)>(bytes: Vec<u8>, emit: &mut F) -> bool {
+    match parser::parse(&bytes) {
+        Ok(message) => {
+            emit(Record {
+                frame_number: None,
+                timestamp: None,
+                source: None,
+                destination: None,
+                bytes,
+                message,
+            });
+            true
+        }
+        Err(e) => {
+            eprint!("ripdump: {}", diagnose(e, &bytes).annotate(&bytes));
+            false
+        }
+    }
+}
+
+/// decode_hex decodes the hex string; the bytes may be separated by whitespaces or colons.
+fn decode_hex(hex: &str) -> Result<Vec<u8>, String> {
+    let digits = hex
+        .trim_start_matches("0x")
+        .chars()
+        .filter(|c| !c.is_whitespace() && *c != ':')
+        .map(|c| c.to_digit(16).map(|digit| digit as u8))
+        .collect::<Option<Vec<u8>>>()
+        .ok_or_else(|| format!("invalid hex string \"{}\"", hex))?;
+    if !digits.len().is_multiple_of(2) {
+        return Err(format!("odd number of hex digits in \"{}\"", hex));
+    }
+    Ok(digits
+        .chunks(2)
+        .map(|pair| pair[0] << 4 | pair[1])
+        .collect())
+}
+
+fn print_record(record: &Record, format: Format) {
+    match format {
+        Format::Text => {
+            if let Some(frame_number) = record.frame_number {
+                println!("# {}", describe_capture(record, frame_number));
+            }
+            println!("{:#}", record.message);
+        }
+        Format::Json => {
+            let json = serde_json::json!({
+                "frame_number": record.frame_number,
+                "timestamp": record.timestamp.map(seconds_since_epoch),
+                "source": record.source.map(|a| a.to_string()),
+                "destination": record.destination.map(|a| a.to_string()),
+                "message": record.message,
+            });
+            println!("{}", json);
+        }
+        Format::Hexdump => {
+            if let Some(frame_number) = record.frame_number {
+                println!("# {}", describe_capture(record, frame_number));
+            }
+            println!("# {}", record.message);
+            print!("{}", hexdump(&record.bytes, 0..0));
+        }
+    }
+}
+
+fn describe_capture(record: &Record, frame_number: u64) -> String {
+    let address = |a: Option<SocketAddr>| a.map(|a| a.to_string()).unwrap_or_default();
+    format!(
+        "frame {} at {:.6}: {} > {}",
+        frame_number,
+        record
+            .timestamp
+            .map(seconds_since_epoch)
+            .unwrap_or_default(),
+        address(record.source),
+        address(record.destination)
+    )
+}
+
+fn seconds_since_epoch(timestamp: SystemTime) -> f64 {
+    timestamp
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs_f64()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{decode_hex, parse_args, Filter, Format, Input, Options};
+    use rip_rs::prefix::Ipv4Prefix;
+    use rip_rs::{command, version};
+    use std::str::FromStr;
+
+    fn args(args: &[&str]) -> impl Iterator<Item = String> {
+        args.iter()
+            .map(|arg| arg.to_string())
+            .collect::<Vec<_>>()
+            .into_iter()
+    }
+
+    #[test]
+    fn test_parse_args() {
+        assert_eq!(
+            parse_args(args(&[
+                "-p",
+                "capture.pcap",
+                "--format",
+                "json",
+                "--command",
+                "response",
+                "--version",
+                "2",
+                "--prefix",
+                "10.0.0.0/8",
+            ]))
+            .unwrap()
+            .unwrap(),
+            Options {
+                input: Input::Pcap("capture.pcap".to_string()),
+                format: Format::Json,
+                filter: Filter {
+                    command: Some(command::Kind::Response),
+                    version: Some(version::Version::Version2),
+                    source: None,
+                    prefix: Some(Ipv4Prefix::from_str("10.0.0.0/8").unwrap()),
+                },
+            }
+        );
+
+        assert_eq!(
+            parse_args(args(&["02020000"])).unwrap().unwrap().input,
+            Input::Hex(vec!["02020000".to_string()])
+        );
+        assert_eq!(parse_args(args(&["-f", "text", "--help"])).unwrap(), None);
+        assert!(parse_args(args(&["--format", "yaml"])).is_err());
+        assert!(parse_args(args(&["--command", "respond"])).is_err());
+        assert!(parse_args(args(&["-r", "packet.bin", "02020000"])).is_err());
+        assert!(parse_args(args(&["--raw"])).is_err());
+    }
+
+    #[test]
+    fn test_decode_hex() {
+        assert_eq!(decode_hex("0x02020000").unwrap(), vec![2, 2, 0, 0]);
+        assert_eq!(decode_hex("02 02:00 00").unwrap(), vec![2, 2, 0, 0]);
+        assert!(decode_hex("020").is_err());
+        assert!(decode_hex("02zz").is_err());
+        assert_eq!(
+            decode_hex("aé0"),
+            Err("invalid hex string \"aé0\"".to_string())
+        );
+    }
+}
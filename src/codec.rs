@@ -0,0 +1,165 @@
+use crate::message::ParsedMessage;
+use crate::packet::Packet;
+use crate::parser::{self, ParseError, ParseOptions, ParsedPacket};
+use crate::serializer::{put_message, put_v1_packet, put_v2_packet, SerializeError};
+use crate::{v1, v2};
+use bytes::BytesMut;
+use std::io;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum CodecError {
+    #[error("I/O error: {0}")]
+    Io(#[from] io::Error),
+    #[error("failed to parse the datagram: {0}")]
+    Parse(#[from] ParseError),
+    #[error("failed to serialize the packet: {0}")]
+    Serialize(#[from] SerializeError),
+}
+
+/// RipCodec is a codec of RIP datagrams for `tokio_util::udp::UdpFramed`.
+/// Each datagram is decoded as a whole into a [`ParsedMessage`].
+/// RIPng is not supported since this crate doesn't implement it.
+#[derive(PartialEq, Eq, Copy, Clone, Debug, Default)]
+pub struct RipCodec {
+    options: ParseOptions,
+}
+
+impl RipCodec {
+    pub fn new() -> Self {
+        RipCodec::default()
+    }
+
+    /// with_parse_options makes a codec that decodes the datagrams with the options.
+    pub fn with_parse_options(options: ParseOptions) -> Self {
+        RipCodec { options }
+    }
+}
+
+impl tokio_util::codec::Decoder for RipCodec {
+    type Item = ParsedMessage;
+    type Error = CodecError;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        if src.is_empty() {
+            return Ok(None);
+        }
+
+        let datagram = src.split();
+        let (message, _) = parser::parse_with_options(&datagram, &self.options)?;
+        Ok(Some(message))
+    }
+}
+
+impl tokio_util::codec::Encoder<Packet<v1::Entry>> for RipCodec {
+    type Error = CodecError;
+
+    fn encode(&mut self, item: Packet<v1::Entry>, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        put_v1_packet(&item, dst)?;
+        Ok(())
+    }
+}
+
+impl tokio_util::codec::Encoder<Packet<v2::Entry>> for RipCodec {
+    type Error = CodecError;
+
+    fn encode(&mut self, item: Packet<v2::Entry>, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        put_v2_packet(&item, dst)?;
+        Ok(())
+    }
+}
+
+impl tokio_util::codec::Encoder<ParsedMessage> for RipCodec {
+    type Error = CodecError;
+
+    fn encode(&mut self, item: ParsedMessage, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        put_message(&item, dst)?;
+        Ok(())
+    }
+}
+
+impl tokio_util::codec::Encoder<ParsedPacket> for RipCodec {
+    type Error = CodecError;
+
+    fn encode(&mut self, item: ParsedPacket, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        match item {
+            ParsedPacket::V1(packet) => self.encode(packet, dst),
+            ParsedPacket::V2(packet) => self.encode(packet, dst),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::codec::{CodecError, RipCodec};
+    use crate::header::Header;
+    use crate::message::{ParsedMessage, RipMessage, UpdateHeader};
+    use crate::metric::Metric;
+    use crate::packet::Packet;
+    use crate::parser::{ParseError, ParseOptions, TrailingBytesPolicy};
+    use crate::{address_family, command, v1, version};
+    use bytes::BytesMut;
+    use std::net::Ipv4Addr;
+    use tokio_util::codec::{Decoder, Encoder};
+
+    #[test]
+    fn test_encode_and_decode() {
+        let packet = Packet::make_v1_packet(
+            Header::new(command::Kind::Response, version::Version::Version1),
+            vec![v1::Entry::new(
+                address_family::Identifier::IP,
+                Ipv4Addr::new(192, 0, 2, 100),
+                Metric::from_raw(1),
+            )],
+        )
+        .unwrap();
+
+        let mut codec = RipCodec::new();
+        let mut buf = BytesMut::new();
+        codec.encode(packet, &mut buf).unwrap();
+        assert_eq!(buf.len(), 24);
+
+        match codec.decode(&mut buf).unwrap().unwrap() {
+            ParsedMessage::V1(RipMessage::Response(entries)) => {
+                assert_eq!(entries[0].get_ip_address(), Ipv4Addr::new(192, 0, 2, 100));
+            }
+            message => panic!("unexpected message {:?}", message),
+        }
+        assert!(buf.is_empty());
+        assert!(codec.decode(&mut buf).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_encode_and_decode_message() {
+        let message = ParsedMessage::V2(RipMessage::UpdateAck(UpdateHeader::new(false, 7)));
+
+        let mut codec = RipCodec::new();
+        let mut buf = BytesMut::new();
+        codec
+            .encode(
+                ParsedMessage::V2(RipMessage::UpdateAck(UpdateHeader::new(false, 7))),
+                &mut buf,
+            )
+            .unwrap();
+        assert_eq!(buf.to_vec(), vec![11, 2, 0, 0, 1, 0, 0, 7]);
+
+        assert_eq!(codec.decode(&mut buf).unwrap().unwrap(), message);
+    }
+
+    #[test]
+    fn test_decode_invalid_datagram() {
+        let mut codec = RipCodec::new();
+        let mut buf = BytesMut::from(&[2, 2, 0, 0, 0][..]);
+        assert!(matches!(
+            codec.decode(&mut buf),
+            Err(CodecError::Parse(ParseError::TrailingBytes(1, 4)))
+        ));
+        assert!(buf.is_empty());
+
+        let mut codec = RipCodec::with_parse_options(
+            ParseOptions::new().trailing_bytes_policy(TrailingBytesPolicy::Ignore),
+        );
+        let mut buf = BytesMut::from(&[2, 2, 0, 0, 0][..]);
+        assert!(codec.decode(&mut buf).unwrap().is_some());
+    }
+}
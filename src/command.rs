@@ -1,9 +1,18 @@
-use crate::parser::Parsed;
-use crate::serializer::SerializeError::UnknownCommandKind;
-use crate::serializer::{Serializable, SerializeError};
+use crate::parser::{Parsable, Parsed};
+use crate::serializer::{write_slice, Serializable, SerializeError};
 use crate::{byte_reader, parser::ParseError};
+use std::fmt;
+use std::str::FromStr;
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq, Clone)]
+pub enum KindError {
+    #[error("unknown command name \"{0}\"")]
+    InvalidNotation(String),
+}
 
 #[derive(PartialEq, Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Kind {
     Invalid,
     Request,                  // RFC1058
@@ -17,7 +26,7 @@ pub enum Kind {
     UpdateRequest,            // RFC2091
     UpdateResponse,           // RFC2091
     UpdateAcknowledge,        // RFC2091
-    Unknown,
+    Unknown(u8),
 }
 
 impl Kind {
@@ -35,56 +44,125 @@ impl Kind {
             9 => Kind::UpdateRequest,
             10 => Kind::UpdateResponse,
             11 => Kind::UpdateAcknowledge,
-            _ => Kind::Unknown,
+            _ => Kind::Unknown(value),
         }
     }
 
-    pub fn to_u8(&self) -> Option<u8> {
+    pub fn to_u8(&self) -> u8 {
         match self {
-            Kind::Invalid => Some(0),
-            Kind::Request => Some(1),
-            Kind::Response => Some(2),
-            Kind::TraceOn => Some(3),
-            Kind::TraceOff => Some(4),
-            Kind::Reserved => Some(5),
-            Kind::TriggeredRequest => Some(6),
-            Kind::TriggeredResponse => Some(7),
-            Kind::TriggeredAcknowledgement => Some(8),
-            Kind::UpdateRequest => Some(9),
-            Kind::UpdateResponse => Some(10),
-            Kind::UpdateAcknowledge => Some(11),
-            Kind::Unknown => None,
+            Kind::Invalid => 0,
+            Kind::Request => 1,
+            Kind::Response => 2,
+            Kind::TraceOn => 3,
+            Kind::TraceOff => 4,
+            Kind::Reserved => 5,
+            Kind::TriggeredRequest => 6,
+            Kind::TriggeredResponse => 7,
+            Kind::TriggeredAcknowledgement => 8,
+            Kind::UpdateRequest => 9,
+            Kind::UpdateResponse => 10,
+            Kind::UpdateAcknowledge => 11,
+            Kind::Unknown(value) => *value,
         }
     }
 
-    pub(crate) fn parse(cursor: usize, bytes: &[u8]) -> Result<Parsed<Kind>, ParseError> {
-        let (command_byte, cursor) = byte_reader::read(cursor, bytes)?;
+    /// allows_empty_entries returns whether a packet of the command may have no RIP entry; e.g. a response from
+    /// a router that has nothing to advertise, or an acknowledgement on a demand circuit.
+    /// It is false for the invalid, the reserved and the unknown commands.
+    pub fn allows_empty_entries(&self) -> bool {
+        !matches!(self, Kind::Invalid | Kind::Reserved | Kind::Unknown(_))
+    }
+
+    /// has_update_header returns whether a message of the command has the update header (RFC1582 and RFC2091)
+    /// between the RIP header and the RIP entries.
+    pub fn has_update_header(&self) -> bool {
+        matches!(
+            self,
+            Kind::TriggeredRequest
+                | Kind::TriggeredResponse
+                | Kind::TriggeredAcknowledgement
+                | Kind::UpdateRequest
+                | Kind::UpdateResponse
+                | Kind::UpdateAcknowledge
+        )
+    }
+}
+
+/// The name of the command is the one in the text format, e.g. `response` or `update-ack`.
+impl fmt::Display for Kind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Kind::Invalid => write!(f, "invalid"),
+            Kind::Request => write!(f, "request"),
+            Kind::Response => write!(f, "response"),
+            Kind::TraceOn => write!(f, "trace-on"),
+            Kind::TraceOff => write!(f, "trace-off"),
+            Kind::Reserved => write!(f, "reserved"),
+            Kind::TriggeredRequest => write!(f, "triggered-request"),
+            Kind::TriggeredResponse => write!(f, "triggered-response"),
+            Kind::TriggeredAcknowledgement => write!(f, "triggered-ack"),
+            Kind::UpdateRequest => write!(f, "update-request"),
+            Kind::UpdateResponse => write!(f, "update-response"),
+            Kind::UpdateAcknowledge => write!(f, "update-ack"),
+            Kind::Unknown(value) => write!(f, "unknown({})", value),
+        }
+    }
+}
 
-        let command = match Kind::from_u8(command_byte) {
-            Kind::Unknown => {
-                return Err(ParseError::UnknownCommandKind(command_byte, cursor));
-            }
-            _command => _command,
+impl FromStr for Kind {
+    type Err = KindError;
+
+    /// from_str accepts the name that Display gives, e.g. `response` or `unknown(12)`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let kind = match s {
+            "invalid" => Kind::Invalid,
+            "request" => Kind::Request,
+            "response" => Kind::Response,
+            "trace-on" => Kind::TraceOn,
+            "trace-off" => Kind::TraceOff,
+            "reserved" => Kind::Reserved,
+            "triggered-request" => Kind::TriggeredRequest,
+            "triggered-response" => Kind::TriggeredResponse,
+            "triggered-ack" => Kind::TriggeredAcknowledgement,
+            "update-request" => Kind::UpdateRequest,
+            "update-response" => Kind::UpdateResponse,
+            "update-ack" => Kind::UpdateAcknowledge,
+            _ => s
+                .strip_prefix("unknown(")
+                .and_then(|value| value.strip_suffix(')'))
+                .and_then(|value| u8::from_str(value).ok())
+                .map(Kind::from_u8)
+                .filter(|kind| matches!(kind, Kind::Unknown(_)))
+                .ok_or_else(|| KindError::InvalidNotation(s.to_string()))?,
         };
+        Ok(kind)
+    }
+}
 
-        Ok((command, cursor))
+impl Parsable for Kind {
+    fn parse(cursor: usize, bytes: &[u8]) -> Result<Parsed<Kind>, ParseError> {
+        let (command_byte, cursor) = byte_reader::read(cursor, bytes)?;
+        Ok((Kind::from_u8(command_byte), cursor))
     }
 }
 
 impl Serializable for Kind {
-    fn to_bytes(&self) -> Result<Vec<u8>, SerializeError> {
-        match self.to_u8() {
-            Some(byte) => Ok(vec![byte]),
-            None => Err(UnknownCommandKind),
-        }
+    fn serialized_len(&self) -> usize {
+        1
+    }
+
+    fn write_bytes(&self, buf: &mut [u8]) -> Result<usize, SerializeError> {
+        write_slice(&[self.to_u8()], buf)
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::command::Kind;
-    use crate::parser::ParseError;
-    use crate::serializer::{Serializable, SerializeError};
+    use crate::command::{Kind, KindError};
+    use crate::parser;
+    use crate::parser::{Parsable, ParseError};
+    use crate::serializer::Serializable;
+    use std::str::FromStr;
 
     #[test]
     fn test_parse() {
@@ -125,12 +203,53 @@ mod tests {
         assert_eq!(kind, Kind::UpdateAcknowledge);
         assert_eq!(cursor, 1);
 
+        let (kind, cursor) = Kind::parse(0, vec![0xff].as_slice()).unwrap();
+        assert_eq!(kind, Kind::Unknown(0xff));
+        assert_eq!(cursor, 1);
+
         assert_eq!(
-            Kind::parse(0, vec![0xff].as_slice()).unwrap_err(),
-            ParseError::UnknownCommandKind(0xff, 1)
+            Kind::parse(1, vec![0x02].as_slice()).unwrap_err(),
+            ParseError::InsufficientInputBytesLength(1)
         );
     }
 
+    #[test]
+    fn test_parse_and_serialize_unknown_command_round_trip() {
+        let bytes = vec![
+            12, 2, 0, 0, //
+            0, 2, 1, 2, //
+            192, 0, 2, 100, //
+            255, 255, 255, 0, //
+            192, 0, 2, 111, //
+            0, 0, 0, 1, //
+        ];
+        let packet = parser::parse_packet(bytes.as_slice()).unwrap();
+        assert_eq!(packet.get_header().get_command(), Kind::Unknown(12));
+        assert_eq!(packet.to_bytes().unwrap(), bytes);
+    }
+
+    #[test]
+    fn test_from_u8_and_to_u8_round_trip() {
+        for value in 0..=u8::MAX {
+            assert_eq!(Kind::from_u8(value).to_u8(), value);
+        }
+        assert_eq!(Kind::from_u8(12), Kind::Unknown(12));
+    }
+
+    #[test]
+    fn test_from_str_and_to_string_round_trip() {
+        for value in 0..=u8::MAX {
+            let kind = Kind::from_u8(value);
+            assert_eq!(Kind::from_str(&kind.to_string()), Ok(kind));
+        }
+        for name in ["respond", "unknown(1)", "unknown(256)", "unknown(12"] {
+            assert_eq!(
+                Kind::from_str(name),
+                Err(KindError::InvalidNotation(name.to_string()))
+            );
+        }
+    }
+
     #[test]
     fn test_to_bytes() {
         assert_eq!(Kind::Invalid.to_bytes().unwrap(), vec![0]);
@@ -146,9 +265,7 @@ mod tests {
         assert_eq!(Kind::UpdateResponse.to_bytes().unwrap(), vec![10]);
         assert_eq!(Kind::UpdateAcknowledge.to_bytes().unwrap(), vec![11]);
 
-        assert_eq!(
-            Kind::Unknown.to_bytes().unwrap_err(),
-            SerializeError::UnknownCommandKind
-        );
+        assert_eq!(Kind::Unknown(12).to_bytes().unwrap(), vec![12]);
+        assert_eq!(Kind::Unknown(0xff).to_bytes().unwrap(), vec![0xff]);
     }
 }
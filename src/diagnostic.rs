@@ -0,0 +1,429 @@
+use crate::message::UPDATE_HEADER_LENGTH;
+use crate::packet::{ENTRY_LENGTH, HEADER_LENGTH};
+use crate::parser::ParseError;
+use crate::{command, version};
+use std::fmt;
+use std::fmt::Write;
+use std::ops::Range;
+
+/// Diagnostic describes where a [`ParseError`] happened in the packet:
+/// the name of the field (e.g. `entry[3].subnet_mask`), the index of the entry and the byte range.
+#[derive(PartialEq, Clone, Debug)]
+pub struct Diagnostic {
+    field: String,
+    entry_index: Option<usize>,
+    span: Range<usize>,
+    error: ParseError,
+}
+
+impl Diagnostic {
+    pub fn get_field(&self) -> &str {
+        &self.field
+    }
+
+    pub fn get_entry_index(&self) -> Option<usize> {
+        self.entry_index
+    }
+
+    pub fn get_span(&self) -> Range<usize> {
+        self.span.clone()
+    }
+
+    pub fn get_error(&self) -> &ParseError {
+        &self.error
+    }
+
+    /// annotate renders the diagnostic message followed by the hexdump of the packet that marks the span.
+    pub fn annotate(&self, bytes: &[u8]) -> String {
+        format!("{}\n{}", self, hexdump(bytes, self.get_span()))
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} (bytes {}..{}): {}",
+            self.field, self.span.start, self.span.end, self.error
+        )
+    }
+}
+
+/// diagnose makes a [`Diagnostic`] of the error that was returned by parsing the given bytes.
+/// The span is the one that the error carries, and the field that contains the head of the span is named by
+/// the layout of the message that [`crate::parser::parse`] reads: the version byte tells the RIPv1/RIPv2 entry layout,
+/// and the command byte tells whether the update header precedes the entries.
+pub fn diagnose(error: ParseError, bytes: &[u8]) -> Diagnostic {
+    let version = bytes.get(1).map_or(version::Version::MustBeDiscarded, |b| {
+        version::Version::from_u8(*b)
+    });
+    let entries_start = entries_start(bytes);
+    let span = error.span().unwrap_or(0..bytes.len());
+
+    let (field, entry_index) = match &error {
+        ParseError::EmptyRIPEntry(_) => ("entries".to_string(), None),
+        ParseError::MaxRIPEntriesNumberExceeded(_) | ParseError::TrailingBytes(_, _) => {
+            let entry_index = (span.start.max(entries_start) - entries_start) / ENTRY_LENGTH;
+            (format!("entry[{}]", entry_index), Some(entry_index))
+        }
+        ParseError::PacketTooLong(_, _) | ParseError::InvalidPacket(_) => {
+            ("packet".to_string(), None)
+        }
+        ParseError::UnexpectedPayload(_) => ("payload".to_string(), None),
+        _ => locate_field(version, entries_start, span.start),
+    };
+
+    Diagnostic {
+        field,
+        entry_index,
+        span,
+        error,
+    }
+}
+
+/// entries_start returns the offset of the RIP entries; they follow the update header if the command has that.
+fn entries_start(bytes: &[u8]) -> usize {
+    let command = bytes
+        .first()
+        .map_or(command::Kind::Invalid, |b| command::Kind::from_u8(*b));
+    if command.has_update_header() {
+        HEADER_LENGTH + UPDATE_HEADER_LENGTH
+    } else {
+        HEADER_LENGTH
+    }
+}
+
+/// locate_field names the field that contains the byte at the offset, and returns it with the index of the entry.
+/// The bytes after the header are named `body` when the version has no known entry layout.
+fn locate_field(
+    version: version::Version,
+    entries_start: usize,
+    offset: usize,
+) -> (String, Option<usize>) {
+    if offset < HEADER_LENGTH {
+        let name = header_fields()
+            .iter()
+            .find(|(_, span)| span.contains(&offset))
+            .map_or("must_be_zero", |(name, _)| name);
+        return (format!("header.{}", name), None);
+    }
+    if offset < entries_start {
+        let name = update_header_fields()
+            .iter()
+            .find(|(_, span)| span.contains(&(offset - HEADER_LENGTH)))
+            .map_or("sequence_number", |(name, _)| name);
+        return (format!("update_header.{}", name), None);
+    }
+
+    let Some(fields) = entry_fields(version) else {
+        return ("body".to_string(), None);
+    };
+    let entry_index = (offset - entries_start) / ENTRY_LENGTH;
+    let relative_offset = (offset - entries_start) % ENTRY_LENGTH;
+    let name = fields
+        .iter()
+        .find(|(_, span)| span.contains(&relative_offset))
+        .map_or("metric", |(name, _)| name);
+
+    (
+        format!("entry[{}].{}", entry_index, name),
+        Some(entry_index),
+    )
+}
+
+/// header_fields returns the names and the byte ranges of the fields in the RIP header.
+pub(crate) fn header_fields() -> &'static [(&'static str, Range<usize>)] {
+    &[("command", 0..1), ("version", 1..2), ("must_be_zero", 2..4)]
+}
+
+/// update_header_fields returns the names and the relative byte ranges of the fields in the update header.
+fn update_header_fields() -> &'static [(&'static str, Range<usize>)] {
+    &[
+        ("version", 0..1),
+        ("flush", 1..2),
+        ("sequence_number", 2..4),
+    ]
+}
+
+/// entry_fields returns the names and the relative byte ranges of the fields in a RIP entry.
+/// It returns None for the versions that don't define the entry layout.
+pub(crate) fn entry_fields(
+    version: version::Version,
+) -> Option<&'static [(&'static str, Range<usize>)]> {
+    match version {
+        version::Version::Version1 => Some(&[
+            ("address_family_identifier", 0..2),
+            ("must_be_zero", 2..4),
+            ("ip_address", 4..8),
+            ("must_be_zero", 8..16),
+            ("metric", 16..20),
+        ]),
+        version::Version::Version2 => Some(&[
+            ("address_family_identifier", 0..2),
+            ("route_tag", 2..4),
+            ("ip_address", 4..8),
+            ("subnet_mask", 8..12),
+            ("next_hop", 12..16),
+            ("metric", 16..20),
+        ]),
+        version::Version::MustBeDiscarded | version::Version::Unknown(_) => None,
+    }
+}
+
+/// hexdump renders the bytes as a hexdump that has a line for the header, the update header if the command has that,
+/// and each entry.
+/// The bytes within the span are marked with `^^`, and the bytes that the span requires beyond the end of
+/// the input are reported as missing.
+pub fn hexdump(bytes: &[u8], span: Range<usize>) -> String {
+    let mut dumped = String::new();
+
+    let entries_start = entries_start(bytes);
+    let mut head = 0;
+    while head < bytes.len() || head == 0 {
+        let (label, tail) = if head < HEADER_LENGTH {
+            ("header".to_string(), HEADER_LENGTH.min(bytes.len()))
+        } else if head < entries_start {
+            ("update_header".to_string(), entries_start.min(bytes.len()))
+        } else {
+            (
+                format!("entry[{}]", (head - entries_start) / ENTRY_LENGTH),
+                (head + ENTRY_LENGTH).min(bytes.len()),
+            )
+        };
+
+        let hex = bytes[head..tail]
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect::<Vec<_>>()
+            .join(" ");
+        let _ = writeln!(
+            dumped,
+            "{:04x}  {:<width$}  {}",
+            head,
+            hex,
+            label,
+            width = ENTRY_LENGTH * 3 - 1
+        );
+
+        if span.start < tail && head < span.end {
+            let marker = (head..tail)
+                .map(|i| if span.contains(&i) { "^^" } else { "  " })
+                .collect::<Vec<_>>()
+                .join(" ");
+            let _ = writeln!(dumped, "      {}", marker.trim_end());
+        }
+
+        if tail <= head {
+            break;
+        }
+        head = tail;
+    }
+
+    if span.end > bytes.len() {
+        let missing = span.end - span.start.max(bytes.len());
+        let _ = writeln!(
+            dumped,
+            "      ({} byte(s) missing at {})",
+            missing,
+            span.start.max(bytes.len())
+        );
+    }
+
+    dumped
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::diagnostic::{diagnose, hexdump};
+    use crate::parser;
+    use crate::parser::ParseError;
+
+    #[test]
+    fn test_diagnose_v2_entry_field() {
+        let bytes = vec![
+            2, 2, 0, 0, //
+            0, 2, 1, 2, //
+            192, 0, 2, 100, //
+            255, 255, 255, 0, //
+            192, 0, 2, 111, //
+            4, 3, 2, 1, //
+            0, 1, 0, 0, // unknown address family identifier
+            192, 0, 2, 101, //
+            255, 255, 255, 0, //
+            192, 0, 2, 111, //
+            0, 0, 0, 1, //
+        ];
+        let err = parser::parse(bytes.as_slice()).unwrap_err();
+        let diagnostic = diagnose(err, bytes.as_slice());
+
+        assert_eq!(diagnostic.get_field(), "entry[1].address_family_identifier");
+        assert_eq!(diagnostic.get_entry_index(), Some(1));
+        assert_eq!(diagnostic.get_span(), 24..26);
+        assert_eq!(
+            diagnostic.get_error(),
+            &ParseError::UnknownAddressFamilyIdentifier(1, 24)
+        );
+        assert_eq!(
+            diagnostic.to_string(),
+            "entry[1].address_family_identifier (bytes 24..26): encountered the unknown address family identifier 1; at 24 byte"
+        );
+    }
+
+    #[test]
+    fn test_diagnose_v1_must_be_zero_byte() {
+        let bytes = vec![
+            2, 1, 0, 0, //
+            0, 2, 0, 0, //
+            192, 0, 2, 100, //
+            0, 0, 1, 0, // the third byte is not zero
+            0, 0, 0, 0, //
+            4, 3, 2, 1, //
+        ];
+        let err = parser::parse(bytes.as_slice()).unwrap_err();
+        let diagnostic = diagnose(err, bytes.as_slice());
+
+        assert_eq!(diagnostic.get_field(), "entry[0].must_be_zero");
+        assert_eq!(diagnostic.get_entry_index(), Some(0));
+        assert_eq!(diagnostic.get_span(), 14..15);
+    }
+
+    #[test]
+    fn test_diagnose_header_and_truncation() {
+        let bytes = vec![2, 2, 1, 0];
+        let err = parser::parse(bytes.as_slice()).unwrap_err();
+        let diagnostic = diagnose(err, bytes.as_slice());
+        assert_eq!(diagnostic.get_field(), "header.must_be_zero");
+        assert_eq!(diagnostic.get_entry_index(), None);
+        assert_eq!(diagnostic.get_span(), 2..3);
+        assert_eq!(diagnostic.get_error(), &ParseError::NotZeroByte(1, 2));
+
+        let bytes = vec![
+            2, 2, 0, 0, //
+            0, 2, 0, 0, //
+            192, 0, 2, 100, //
+            255, 255, 255, 0, //
+            0, 0, 0, 0, //
+            4, 3, 2, // missing a trailing byte
+        ];
+        let err = parser::parse(bytes.as_slice()).unwrap_err();
+        let diagnostic = diagnose(err, bytes.as_slice());
+        assert_eq!(diagnostic.get_field(), "entry[0]");
+        assert_eq!(diagnostic.get_span(), 4..23);
+    }
+
+    #[test]
+    fn test_diagnose_update_response() {
+        let bytes = vec![
+            10, 2, 0, 0, //
+            1, 0, 0, 7, // update header
+            0, 2, 0, 0, //
+            192, 0, 2, 0, //
+            255, 255, 255, 0, //
+            0, 0, 0, 0, //
+            0, 0, 0, 1, //
+            0, 1, 0, 0, // unknown address family identifier
+            192, 0, 2, 1, //
+            255, 255, 255, 0, //
+            0, 0, 0, 0, //
+            0, 0, 0, 1, //
+        ];
+        let err = parser::parse(bytes.as_slice()).unwrap_err();
+        let diagnostic = diagnose(err, bytes.as_slice());
+        assert_eq!(diagnostic.get_field(), "entry[1].address_family_identifier");
+        assert_eq!(diagnostic.get_entry_index(), Some(1));
+        assert_eq!(diagnostic.get_span(), 28..30);
+
+        let err = parser::parse(&bytes[..30]).unwrap_err();
+        let diagnostic = diagnose(err, &bytes[..30]);
+        assert_eq!(diagnostic.get_field(), "entry[1]");
+        assert_eq!(diagnostic.get_span(), 28..30);
+
+        let mut bytes = bytes[..28].to_vec();
+        bytes[4] = 2;
+        let err = parser::parse(bytes.as_slice()).unwrap_err();
+        let diagnostic = diagnose(err, bytes.as_slice());
+        assert_eq!(diagnostic.get_field(), "update_header.version");
+        assert_eq!(diagnostic.get_entry_index(), None);
+        assert_eq!(diagnostic.get_span(), 4..5);
+        assert_eq!(
+            diagnostic.annotate(&bytes[..8]),
+            [
+                "update_header.version (bytes 4..5): update header version 2 is not supported; at 4 byte\n",
+                "0000  0a 02 00 00                                                  header\n",
+                "0004  02 00 00 07                                                  update_header\n",
+                "      ^^\n",
+            ]
+            .concat()
+        );
+
+        let bytes = vec![11, 2, 0, 0, 1, 0, 0, 7, 0];
+        let err = parser::parse(bytes.as_slice()).unwrap_err();
+        assert_eq!(diagnose(err, bytes.as_slice()).get_field(), "payload");
+    }
+
+    #[test]
+    fn test_diagnose_without_entry_layout() {
+        let diagnostic = diagnose(ParseError::NotZeroByte(1, 30), vec![2, 9, 0, 0].as_slice());
+        assert_eq!(diagnostic.get_field(), "body");
+        assert_eq!(diagnostic.get_entry_index(), None);
+        assert_eq!(diagnostic.get_span(), 30..31);
+
+        let diagnostic = diagnose(
+            ParseError::MaxRIPEntriesNumberExceeded(504),
+            vec![2, 2, 0, 0].as_slice(),
+        );
+        assert_eq!(diagnostic.get_field(), "entry[25]");
+        assert_eq!(diagnostic.get_entry_index(), Some(25));
+        assert_eq!(diagnostic.get_span(), 504..524);
+    }
+
+    #[test]
+    fn test_hexdump() {
+        let bytes = vec![
+            2, 2, 0, 0, //
+            0, 2, 1, 2, //
+            192, 0, 2, 100, //
+            255, 255, 255, 0, //
+            192, 0, 2, 111, //
+            4, 3, 2, 1, //
+        ];
+
+        assert_eq!(
+            hexdump(bytes.as_slice(), 12..16),
+            [
+                "0000  02 02 00 00                                                  header\n",
+                "0004  00 02 01 02 c0 00 02 64 ff ff ff 00 c0 00 02 6f 04 03 02 01  entry[0]\n",
+                "                              ^^ ^^ ^^ ^^\n",
+            ]
+            .concat()
+        );
+
+        assert_eq!(
+            hexdump(&bytes[..22], 20..24),
+            [
+                "0000  02 02 00 00                                                  header\n",
+                "0004  00 02 01 02 c0 00 02 64 ff ff ff 00 c0 00 02 6f 04 03        entry[0]\n",
+                "                                                      ^^ ^^\n",
+                "      (2 byte(s) missing at 22)\n",
+            ]
+            .concat()
+        );
+    }
+
+    #[test]
+    fn test_annotate() {
+        let bytes = vec![2, 2, 0, 1];
+        let err = parser::parse(bytes.as_slice()).unwrap_err();
+        let diagnostic = diagnose(err, bytes.as_slice());
+
+        assert_eq!(
+            diagnostic.annotate(bytes.as_slice()),
+            [
+                "header.must_be_zero (bytes 3..4): the byte must be zero but was 1 at 3 byte\n",
+                "0000  02 02 00 01                                                  header\n",
+                "               ^^\n",
+            ]
+            .concat()
+        );
+    }
+}
@@ -0,0 +1,431 @@
+use crate::diagnostic::{entry_fields, header_fields};
+use crate::packet::{ENTRY_LENGTH, HEADER_LENGTH};
+use crate::parser::{walk_entries, ParseError};
+use crate::{address_family, command, header, v1, v2, version};
+use std::fmt::Write;
+use std::net::Ipv4Addr;
+use std::ops::Range;
+
+/// Node is a node of the dissection tree.
+/// A leaf node is a field that has the decoded value, and an inner node is the header or an entry.
+#[derive(PartialEq, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Node {
+    name: String,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    value: Option<String>,
+    range: Range<usize>,
+    #[cfg_attr(feature = "serde", serde(serialize_with = "serialize_hex"))]
+    bytes: Vec<u8>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(
+            serialize_with = "serialize_error",
+            skip_serializing_if = "Option::is_none"
+        )
+    )]
+    error: Option<ParseError>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Vec::is_empty"))]
+    children: Vec<Node>,
+}
+
+impl Node {
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn get_value(&self) -> Option<&str> {
+        self.value.as_deref()
+    }
+
+    /// get_range returns the byte range of the node in the packet.
+    /// It can exceed the length of the packet when the packet is truncated.
+    pub fn get_range(&self) -> Range<usize> {
+        self.range.clone()
+    }
+
+    /// get_bytes returns the raw bytes of the node; that is the part of the range which exists in the packet.
+    pub fn get_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// get_error returns the error if the node couldn't be decoded.
+    pub fn get_error(&self) -> Option<&ParseError> {
+        self.error.as_ref()
+    }
+
+    pub fn get_children(&self) -> &[Node] {
+        &self.children
+    }
+}
+
+/// Dissection is a tree of the header and the entries of a RIP packet.
+/// For a malformed packet, it contains the nodes up to the point of failure and the error.
+#[derive(PartialEq, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Dissection {
+    nodes: Vec<Node>,
+    #[cfg_attr(feature = "serde", serde(serialize_with = "serialize_error"))]
+    error: Option<ParseError>,
+}
+
+impl Dissection {
+    pub fn get_nodes(&self) -> &[Node] {
+        &self.nodes
+    }
+
+    pub fn get_error(&self) -> Option<&ParseError> {
+        self.error.as_ref()
+    }
+
+    /// to_text renders the dissection as an indented text.
+    pub fn to_text(&self) -> String {
+        let mut text = String::new();
+        for node in &self.nodes {
+            write_text(&mut text, node, 0);
+        }
+        if let Some(e) = &self.error {
+            let _ = writeln!(text, "error: {}", e);
+        }
+        text
+    }
+}
+
+/// dissect decodes the bytes as a RIP packet into a tree that every field has the name, the decoded value,
+/// the raw bytes and the byte range.
+/// Unlike [`crate::parser::parse`], this doesn't fail on a malformed packet; it decodes the packet up to the
+/// point of failure and reports the error in the [`Dissection`].
+pub fn dissect(bytes: &[u8]) -> Dissection {
+    let mut nodes = vec![];
+
+    let cursor = match header::parse(0, bytes) {
+        Ok((_, cursor)) => {
+            nodes.push(make_node(
+                "header".to_string(),
+                0,
+                header_fields(),
+                bytes,
+                None,
+            ));
+            cursor
+        }
+        Err(e) => {
+            nodes.push(make_node(
+                "header".to_string(),
+                0,
+                header_fields(),
+                bytes,
+                Some(&e),
+            ));
+            return Dissection {
+                nodes,
+                error: Some(e),
+            };
+        }
+    };
+
+    let ver = version::Version::from_u8(bytes[1]);
+    let allow_empty = command::Kind::from_u8(bytes[0]).allows_empty_entries();
+    let result = match ver {
+        version::Version::Version1 => {
+            walk_entries(&v1::EntriesParser {}, cursor, bytes, allow_empty, |_| {})
+        }
+        version::Version::Version2 => {
+            walk_entries(&v2::EntriesParser {}, cursor, bytes, allow_empty, |_| {})
+        }
+        version::Version::MustBeDiscarded => Err(ParseError::MustBeDiscardedVersion(1)),
+        version::Version::Unknown(_) => Err(ParseError::UnknownVersion(1)),
+    };
+
+    let (num_of_entries, error) = match result {
+        Ok(num_of_entries) => (num_of_entries, None),
+        Err(e) => match e {
+            ParseError::MustBeDiscardedVersion(_) | ParseError::UnknownVersion(_) => (0, Some(e)),
+            _ => {
+                let offset = e.offset().unwrap_or(cursor);
+                let num_of_entries = (offset.max(cursor) - cursor) / ENTRY_LENGTH;
+                (num_of_entries, Some(e))
+            }
+        },
+    };
+
+    for i in 0..num_of_entries {
+        nodes.push(make_node(
+            format!("entry[{}]", i),
+            HEADER_LENGTH + i * ENTRY_LENGTH,
+            entry_fields(ver).unwrap_or(&[]),
+            bytes,
+            None,
+        ));
+    }
+
+    if let Some(e) = &error {
+        let failed_entry_head = HEADER_LENGTH + num_of_entries * ENTRY_LENGTH;
+        if e.offset().is_some_and(|offset| offset >= failed_entry_head)
+            && !matches!(
+                e,
+                ParseError::EmptyRIPEntry(_) | ParseError::MaxRIPEntriesNumberExceeded(_)
+            )
+        {
+            nodes.push(make_node(
+                format!("entry[{}]", num_of_entries),
+                failed_entry_head,
+                entry_fields(ver).unwrap_or(&[]),
+                bytes,
+                Some(e),
+            ));
+        }
+    }
+
+    Dissection { nodes, error }
+}
+
+fn make_node(
+    name: String,
+    head: usize,
+    fields: &[(&'static str, Range<usize>)],
+    bytes: &[u8],
+    error: Option<&ParseError>,
+) -> Node {
+    let tail = head + fields.last().map(|(_, span)| span.end).unwrap_or(0);
+    let failed_at = error.and_then(|e| e.offset());
+
+    let mut children = vec![];
+    for (field_name, span) in fields {
+        let range = head + span.start..head + span.end;
+        let field_bytes = slice_within(bytes, &range).to_vec();
+
+        match failed_at {
+            Some(offset) if range.contains(&offset) || range.start > offset => {
+                if range.start <= offset {
+                    children.push(Node {
+                        name: field_name.to_string(),
+                        value: None,
+                        range,
+                        bytes: field_bytes,
+                        error: error.cloned(),
+                        children: vec![],
+                    });
+                }
+            }
+            _ => {
+                children.push(Node {
+                    name: field_name.to_string(),
+                    value: Some(decode_field(field_name, &field_bytes)),
+                    range,
+                    bytes: field_bytes,
+                    error: None,
+                    children: vec![],
+                });
+            }
+        }
+    }
+
+    Node {
+        name,
+        value: None,
+        range: head..tail,
+        bytes: slice_within(bytes, &(head..tail)).to_vec(),
+        error: None,
+        children,
+    }
+}
+
+fn slice_within<'a>(bytes: &'a [u8], range: &Range<usize>) -> &'a [u8] {
+    let start = range.start.min(bytes.len());
+    let end = range.end.min(bytes.len());
+    &bytes[start..end]
+}
+
+fn decode_field(name: &str, bytes: &[u8]) -> String {
+    let value = bytes.iter().fold(0_u64, |acc, b| (acc << 8) + *b as u64);
+    match name {
+        "command" => format!("{:?} ({})", command::Kind::from_u8(value as u8), value),
+        "version" => format!("{:?} ({})", version::Version::from_u8(value as u8), value),
+        "address_family_identifier" => format!(
+            "{:?} ({})",
+            address_family::Identifier::from_u16(value as u16),
+            value
+        ),
+        "ip_address" | "subnet_mask" | "next_hop" => Ipv4Addr::from(value as u32).to_string(),
+        _ => value.to_string(),
+    }
+}
+
+fn write_text(text: &mut String, node: &Node, depth: usize) {
+    let indent = "  ".repeat(depth);
+    let hex = node
+        .bytes
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect::<Vec<_>>()
+        .join(" ");
+    let range = format!("[{}..{}]", node.range.start, node.range.end);
+
+    let _ = match (&node.value, &node.error) {
+        (_, Some(e)) => writeln!(text, "{}{}: <{}> {} {}", indent, node.name, e, range, hex),
+        (Some(value), None) => {
+            writeln!(text, "{}{}: {} {} {}", indent, node.name, value, range, hex)
+        }
+        (None, None) => writeln!(text, "{}{} {}", indent, node.name, range),
+    };
+
+    for child in &node.children {
+        write_text(text, child, depth + 1);
+    }
+}
+
+/// serialize_hex serializes the raw bytes as a hex string, e.g. `02020000`.
+#[cfg(feature = "serde")]
+fn serialize_hex<S: serde::Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_str(
+        &bytes
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect::<String>(),
+    )
+}
+
+/// serialize_error serializes the error as its message.
+#[cfg(feature = "serde")]
+fn serialize_error<S: serde::Serializer>(
+    error: &Option<ParseError>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    match error {
+        Some(e) => serializer.collect_str(e),
+        None => serializer.serialize_none(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dissector::dissect;
+    use crate::parser::ParseError;
+
+    #[test]
+    fn test_dissect_v2_packet() {
+        let bytes = vec![
+            2, 2, 0, 0, //
+            0, 2, 1, 2, //
+            192, 0, 2, 100, //
+            255, 255, 255, 0, //
+            192, 0, 2, 111, //
+            4, 3, 2, 1, //
+        ];
+        let dissection = dissect(bytes.as_slice());
+
+        assert_eq!(dissection.get_error(), None);
+        assert_eq!(dissection.get_nodes().len(), 2);
+
+        let header = &dissection.get_nodes()[0];
+        assert_eq!(header.get_name(), "header");
+        assert_eq!(header.get_range(), 0..4);
+        assert_eq!(header.get_bytes(), &[2, 2, 0, 0]);
+        assert_eq!(header.get_children()[0].get_value(), Some("Response (2)"));
+
+        let entry = &dissection.get_nodes()[1];
+        assert_eq!(entry.get_name(), "entry[0]");
+        assert_eq!(entry.get_range(), 4..24);
+        let subnet_mask = &entry.get_children()[3];
+        assert_eq!(subnet_mask.get_name(), "subnet_mask");
+        assert_eq!(subnet_mask.get_value(), Some("255.255.255.0"));
+        assert_eq!(subnet_mask.get_range(), 12..16);
+        assert_eq!(subnet_mask.get_bytes(), &[255, 255, 255, 0]);
+
+        assert_eq!(
+            dissection.to_text(),
+            [
+                "header [0..4]\n",
+                "  command: Response (2) [0..1] 02\n",
+                "  version: Version2 (2) [1..2] 02\n",
+                "  must_be_zero: 0 [2..4] 00 00\n",
+                "entry[0] [4..24]\n",
+                "  address_family_identifier: IP (2) [4..6] 00 02\n",
+                "  route_tag: 258 [6..8] 01 02\n",
+                "  ip_address: 192.0.2.100 [8..12] c0 00 02 64\n",
+                "  subnet_mask: 255.255.255.0 [12..16] ff ff ff 00\n",
+                "  next_hop: 192.0.2.111 [16..20] c0 00 02 6f\n",
+                "  metric: 67305985 [20..24] 04 03 02 01\n",
+            ]
+            .concat()
+        );
+    }
+
+    #[test]
+    fn test_dissect_malformed_v1_packet() {
+        let bytes = vec![
+            2, 1, 0, 0, //
+            0, 2, 0, 0, //
+            192, 0, 2, 100, //
+            0, 0, 0, 0, //
+            0, 0, 0, 0, //
+            0, 0, 0, 1, //
+            0, 2, 0, 0, //
+            192, 0, 2, 101, //
+            0, 0, 1, 0, // the third byte is not zero
+            0, 0, 0, 0, //
+            0, 0, 0, 2, //
+        ];
+        let dissection = dissect(bytes.as_slice());
+
+        assert_eq!(
+            dissection.get_error(),
+            Some(&ParseError::NotZeroByte(1, 34))
+        );
+        assert_eq!(dissection.get_nodes().len(), 3);
+
+        let failed_entry = &dissection.get_nodes()[2];
+        assert_eq!(failed_entry.get_name(), "entry[1]");
+        let fields = failed_entry.get_children();
+        assert_eq!(fields.len(), 4);
+        assert_eq!(fields[2].get_value(), Some("192.0.2.101"));
+        assert_eq!(fields[3].get_name(), "must_be_zero");
+        assert_eq!(fields[3].get_value(), None);
+        assert_eq!(fields[3].get_error(), Some(&ParseError::NotZeroByte(1, 34)));
+    }
+
+    #[test]
+    fn test_dissect_truncated_header() {
+        let dissection = dissect(vec![2, 2, 0].as_slice());
+
+        assert_eq!(
+            dissection.get_error(),
+            Some(&ParseError::InsufficientInputBytesLength(3))
+        );
+        let header = &dissection.get_nodes()[0];
+        assert_eq!(header.get_bytes(), &[2, 2, 0]);
+        assert_eq!(header.get_children().len(), 3);
+        assert_eq!(header.get_children()[2].get_bytes(), &[0]);
+        assert!(header.get_children()[2].get_error().is_some());
+    }
+
+    #[test]
+    fn test_dissect_unknown_version() {
+        let dissection = dissect(vec![2, 9, 0, 0].as_slice());
+
+        assert_eq!(dissection.get_error(), Some(&ParseError::UnknownVersion(1)));
+        assert_eq!(dissection.get_nodes().len(), 1);
+        assert_eq!(
+            dissection.get_nodes()[0].get_children()[1].get_value(),
+            Some("Unknown(9) (9)")
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde() {
+        let dissection = dissect(vec![2, 2, 0, 1].as_slice());
+
+        assert_eq!(
+            serde_json::to_string(&dissection).unwrap(),
+            concat!(
+                "{\"nodes\":[{\"name\":\"header\",\"range\":{\"start\":0,\"end\":4},\"bytes\":\"02020001\",\"children\":[",
+                "{\"name\":\"command\",\"value\":\"Response (2)\",\"range\":{\"start\":0,\"end\":1},\"bytes\":\"02\"},",
+                "{\"name\":\"version\",\"value\":\"Version2 (2)\",\"range\":{\"start\":1,\"end\":2},\"bytes\":\"02\"},",
+                "{\"name\":\"must_be_zero\",\"range\":{\"start\":2,\"end\":4},\"bytes\":\"0001\",\"error\":\"the byte must be zero but was 1 at 3 byte\"}",
+                "]}],\"error\":\"the byte must be zero but was 1 at 3 byte\"}"
+            )
+        );
+    }
+}
@@ -0,0 +1,328 @@
+use crate::packet::Packet;
+use crate::parser::{self, ParseError, ParsedPacket};
+use crate::serializer::{Serializable, SerializeError};
+use crate::{v1, v2};
+use std::net::{Ipv4Addr, SocketAddrV4};
+use thiserror::Error;
+
+/// The length of the IPv4 header without any option in bytes.
+pub const IPV4_HEADER_LENGTH: usize = 20;
+/// The length of the UDP header in bytes.
+pub const UDP_HEADER_LENGTH: usize = 8;
+/// The protocol number of UDP in the IPv4 header.
+pub const IP_PROTOCOL_UDP: u8 = 17;
+
+/// The maximum length of the UDP payload that an IPv4 packet can carry.
+const MAX_PAYLOAD_LENGTH: usize = u16::MAX as usize - IPV4_HEADER_LENGTH - UDP_HEADER_LENGTH;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum EncapsulationError {
+    #[error("failed to serialize the packet: {0}")]
+    Serialize(#[from] SerializeError),
+    #[error("the payload length {0} exceeds the maximum length of a UDP datagram over IPv4")]
+    PayloadTooLong(usize),
+    #[error("insufficient input bytes length {0} for the IPv4 and UDP headers")]
+    InsufficientLength(usize),
+    #[error("IP version {0} is not IPv4")]
+    NotIpv4(u8),
+    #[error("invalid IPv4 header length {0}")]
+    InvalidHeaderLength(usize),
+    #[error("the IPv4 total length {0} doesn't match the input bytes length {1}")]
+    InvalidTotalLength(usize, usize),
+    #[error("the IPv4 header checksum is invalid")]
+    InvalidHeaderChecksum,
+    #[error("the IPv4 packet is a fragment")]
+    Fragmented,
+    #[error("protocol {0} is not UDP")]
+    NotUdp(u8),
+    #[error("the UDP length {0} doesn't match the IPv4 payload length {1}")]
+    InvalidUdpLength(usize, usize),
+    #[error("the UDP checksum is invalid")]
+    InvalidUdpChecksum,
+    #[error("failed to parse the RIP packet: {0}")]
+    Parse(#[from] ParseError),
+}
+
+/// Datagram is a RIP packet that is decapsulated from an IPv4 packet with the addresses and the TTL.
+#[derive(PartialEq, Debug)]
+pub struct Datagram {
+    source: SocketAddrV4,
+    destination: SocketAddrV4,
+    ttl: u8,
+    packet: ParsedPacket,
+}
+
+impl Datagram {
+    pub fn get_source(&self) -> SocketAddrV4 {
+        self.source
+    }
+
+    pub fn get_destination(&self) -> SocketAddrV4 {
+        self.destination
+    }
+
+    pub fn get_ttl(&self) -> u8 {
+        self.ttl
+    }
+
+    pub fn get_packet(&self) -> &ParsedPacket {
+        &self.packet
+    }
+
+    pub fn into_packet(self) -> ParsedPacket {
+        self.packet
+    }
+}
+
+/// encapsulate wraps the payload in the UDP and IPv4 headers and returns the IPv4 packet with the correct checksums.
+/// The packet has the precedence of the internetwork control, and it is neither fragmented nor has any option.
+pub fn encapsulate(
+    source: SocketAddrV4,
+    destination: SocketAddrV4,
+    ttl: u8,
+    payload: &[u8],
+) -> Result<Vec<u8>, EncapsulationError> {
+    if payload.len() > MAX_PAYLOAD_LENGTH {
+        return Err(EncapsulationError::PayloadTooLong(payload.len()));
+    }
+    let udp_length = (UDP_HEADER_LENGTH + payload.len()) as u16;
+    let total_length = IPV4_HEADER_LENGTH as u16 + udp_length;
+
+    let mut packet = Vec::with_capacity(total_length as usize);
+    // version 4, IHL 5, and the precedence of the internetwork control
+    packet.extend([0x45, 0xc0]);
+    packet.extend(total_length.to_be_bytes());
+    packet.extend([0, 0, 0, 0]);
+    packet.extend([ttl, IP_PROTOCOL_UDP, 0, 0]);
+    packet.extend(source.ip().octets());
+    packet.extend(destination.ip().octets());
+    let header_checksum = checksum(&[&packet]);
+    packet[10..12].copy_from_slice(&header_checksum.to_be_bytes());
+
+    packet.extend(source.port().to_be_bytes());
+    packet.extend(destination.port().to_be_bytes());
+    packet.extend(udp_length.to_be_bytes());
+    packet.extend([0, 0]);
+    packet.extend(payload);
+    let udp_checksum = match udp_checksum(
+        *source.ip(),
+        *destination.ip(),
+        &packet[IPV4_HEADER_LENGTH..],
+    ) {
+        // zero means no checksum in UDP, so it's transmitted as all ones (RFC768).
+        0 => 0xffff,
+        sum => sum,
+    };
+    packet[IPV4_HEADER_LENGTH + 6..IPV4_HEADER_LENGTH + 8]
+        .copy_from_slice(&udp_checksum.to_be_bytes());
+
+    Ok(packet)
+}
+
+pub fn encapsulate_v1_packet(
+    source: SocketAddrV4,
+    destination: SocketAddrV4,
+    ttl: u8,
+    packet: &Packet<v1::Entry>,
+) -> Result<Vec<u8>, EncapsulationError> {
+    encapsulate(source, destination, ttl, &packet.to_bytes()?)
+}
+
+pub fn encapsulate_v2_packet(
+    source: SocketAddrV4,
+    destination: SocketAddrV4,
+    ttl: u8,
+    packet: &Packet<v2::Entry>,
+) -> Result<Vec<u8>, EncapsulationError> {
+    encapsulate(source, destination, ttl, &packet.to_bytes()?)
+}
+
+/// decapsulate validates and strips the IPv4 and UDP headers, and parses the payload by [`parser::parse_packet`].
+/// The IPv4 packet must not be a fragment, and the UDP checksum is verified unless it is zero.
+pub fn decapsulate(bytes: &[u8]) -> Result<Datagram, EncapsulationError> {
+    if bytes.len() < IPV4_HEADER_LENGTH + UDP_HEADER_LENGTH {
+        return Err(EncapsulationError::InsufficientLength(bytes.len()));
+    }
+    if bytes[0] >> 4 != 4 {
+        return Err(EncapsulationError::NotIpv4(bytes[0] >> 4));
+    }
+    let header_length = (bytes[0] & 0x0f) as usize * 4;
+    if header_length < IPV4_HEADER_LENGTH || header_length + UDP_HEADER_LENGTH > bytes.len() {
+        return Err(EncapsulationError::InvalidHeaderLength(header_length));
+    }
+    let total_length = u16::from_be_bytes([bytes[2], bytes[3]]) as usize;
+    if total_length != bytes.len() {
+        return Err(EncapsulationError::InvalidTotalLength(
+            total_length,
+            bytes.len(),
+        ));
+    }
+    if checksum(&[&bytes[..header_length]]) != 0 {
+        return Err(EncapsulationError::InvalidHeaderChecksum);
+    }
+    if u16::from_be_bytes([bytes[6], bytes[7]]) & 0x3fff != 0 {
+        return Err(EncapsulationError::Fragmented);
+    }
+    if bytes[9] != IP_PROTOCOL_UDP {
+        return Err(EncapsulationError::NotUdp(bytes[9]));
+    }
+    let ttl = bytes[8];
+    let source_ip = Ipv4Addr::new(bytes[12], bytes[13], bytes[14], bytes[15]);
+    let destination_ip = Ipv4Addr::new(bytes[16], bytes[17], bytes[18], bytes[19]);
+
+    let udp = &bytes[header_length..];
+    let udp_length = u16::from_be_bytes([udp[4], udp[5]]) as usize;
+    if udp_length != udp.len() {
+        return Err(EncapsulationError::InvalidUdpLength(udp_length, udp.len()));
+    }
+    if udp[6..8] != [0, 0] && udp_checksum(source_ip, destination_ip, udp) != 0 {
+        return Err(EncapsulationError::InvalidUdpChecksum);
+    }
+
+    Ok(Datagram {
+        source: SocketAddrV4::new(source_ip, u16::from_be_bytes([udp[0], udp[1]])),
+        destination: SocketAddrV4::new(destination_ip, u16::from_be_bytes([udp[2], udp[3]])),
+        ttl,
+        packet: parser::parse_packet(&udp[UDP_HEADER_LENGTH..])?,
+    })
+}
+
+/// checksum computes the Internet checksum (RFC1071) of the concatenated chunks.
+/// Each chunk but the last must have even length.
+pub fn checksum(chunks: &[&[u8]]) -> u16 {
+    let mut sum: u32 = 0;
+    for chunk in chunks {
+        for word in chunk.chunks(2) {
+            let word = match word {
+                [high, low] => u16::from_be_bytes([*high, *low]),
+                [high] => u16::from_be_bytes([*high, 0]),
+                _ => 0,
+            };
+            sum += word as u32;
+        }
+    }
+    while sum > 0xffff {
+        sum = (sum & 0xffff) + (sum >> 16);
+    }
+    !(sum as u16)
+}
+
+/// udp_checksum computes the checksum of the UDP datagram with the pseudo header.
+/// It is zero for a datagram that has the correct checksum.
+fn udp_checksum(source: Ipv4Addr, destination: Ipv4Addr, udp: &[u8]) -> u16 {
+    let mut pseudo_header = [0; 12];
+    pseudo_header[0..4].copy_from_slice(&source.octets());
+    pseudo_header[4..8].copy_from_slice(&destination.octets());
+    pseudo_header[9] = IP_PROTOCOL_UDP;
+    pseudo_header[10..12].copy_from_slice(&(udp.len() as u16).to_be_bytes());
+    checksum(&[&pseudo_header, udp])
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::encapsulation::{
+        checksum, decapsulate, encapsulate, encapsulate_v1_packet, EncapsulationError,
+    };
+    use crate::header::Header;
+    use crate::metric::Metric;
+    use crate::packet::Packet;
+    use crate::parser::{ParseError, ParsedPacket};
+    use crate::{address_family, command, v1, version};
+    use std::net::{Ipv4Addr, SocketAddrV4};
+
+    fn v1_packet() -> Packet<v1::Entry> {
+        Packet::make_v1_packet(
+            Header::new(command::Kind::Response, version::Version::Version1),
+            vec![v1::Entry::new(
+                address_family::Identifier::IP,
+                Ipv4Addr::new(192, 0, 2, 0),
+                Metric::from_raw(1),
+            )],
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_encapsulate_and_decapsulate() {
+        let source = SocketAddrV4::new(Ipv4Addr::new(192, 0, 2, 1), 520);
+        let destination = SocketAddrV4::new(Ipv4Addr::new(255, 255, 255, 255), 520);
+        let bytes = encapsulate_v1_packet(source, destination, 1, &v1_packet()).unwrap();
+
+        assert_eq!(
+            bytes[..28],
+            [
+                0x45, 0xc0, 0, 52, //
+                0, 0, 0, 0, //
+                1, 17, 0xf6, 0xf8, //
+                192, 0, 2, 1, //
+                255, 255, 255, 255, //
+                0x02, 0x08, 0x02, 0x08, //
+                0, 32, 0x75, 0x98, //
+            ]
+        );
+        assert_eq!(checksum(&[&bytes[..20]]), 0);
+
+        let datagram = decapsulate(&bytes).unwrap();
+        assert_eq!(datagram.get_source(), source);
+        assert_eq!(datagram.get_destination(), destination);
+        assert_eq!(datagram.get_ttl(), 1);
+        assert_eq!(datagram.into_packet(), ParsedPacket::V1(v1_packet()));
+    }
+
+    #[test]
+    fn test_decapsulate_invalid_packet() {
+        let source = SocketAddrV4::new(Ipv4Addr::new(192, 0, 2, 1), 520);
+        let destination = SocketAddrV4::new(Ipv4Addr::new(224, 0, 0, 9), 520);
+        let bytes = encapsulate(source, destination, 1, &[2, 2, 0, 0, 0]).unwrap();
+
+        assert_eq!(
+            decapsulate(&bytes).unwrap_err(),
+            EncapsulationError::Parse(ParseError::TrailingBytes(1, 4))
+        );
+        assert_eq!(
+            decapsulate(&bytes[..27]).unwrap_err(),
+            EncapsulationError::InsufficientLength(27)
+        );
+        assert_eq!(
+            decapsulate(&bytes[..30]).unwrap_err(),
+            EncapsulationError::InvalidTotalLength(33, 30)
+        );
+
+        let mut broken = bytes.clone();
+        broken[8] = 64;
+        assert_eq!(
+            decapsulate(&broken).unwrap_err(),
+            EncapsulationError::InvalidHeaderChecksum
+        );
+
+        let mut broken = bytes.clone();
+        broken[32] ^= 0xff;
+        assert_eq!(
+            decapsulate(&broken).unwrap_err(),
+            EncapsulationError::InvalidUdpChecksum
+        );
+
+        // the zero UDP checksum is not verified
+        let mut unchecked = broken;
+        unchecked[26..28].fill(0);
+        assert_eq!(
+            decapsulate(&unchecked).unwrap_err(),
+            EncapsulationError::Parse(ParseError::TrailingBytes(1, 4))
+        );
+
+        let mut fragment = bytes;
+        fragment[6] = 0x20; // more fragments
+        fragment[10..12].fill(0);
+        let header_checksum = checksum(&[&fragment[..20]]);
+        fragment[10..12].copy_from_slice(&header_checksum.to_be_bytes());
+        assert_eq!(
+            decapsulate(&fragment).unwrap_err(),
+            EncapsulationError::Fragmented
+        );
+
+        assert_eq!(
+            encapsulate(source, destination, 1, &vec![0; 65508]).unwrap_err(),
+            EncapsulationError::PayloadTooLong(65508)
+        );
+    }
+}
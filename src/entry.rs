@@ -0,0 +1,151 @@
+use crate::address_family;
+use crate::metric::Metric;
+use crate::route_tag::RouteTag;
+use crate::{v1, v2};
+use std::net::Ipv4Addr;
+
+/// RouteEntry is the version-agnostic view of a RIP entry.
+/// The fields that the RIP version doesn't have (e.g. the subnet mask of RIPv1) are `None`.
+pub trait RouteEntry {
+    fn address_family_identifier(&self) -> address_family::Identifier;
+
+    /// destination returns the IP address of the destination.
+    fn destination(&self) -> Ipv4Addr;
+
+    fn subnet_mask(&self) -> Option<Ipv4Addr>;
+
+    fn next_hop(&self) -> Option<Ipv4Addr>;
+
+    fn route_tag(&self) -> Option<RouteTag>;
+
+    fn metric(&self) -> Metric;
+}
+
+/// AnyEntry is a RIP entry of either version.
+#[derive(PartialEq, Debug)]
+pub enum AnyEntry {
+    V1(v1::Entry),
+    V2(v2::Entry),
+}
+
+impl AnyEntry {
+    fn as_route_entry(&self) -> &dyn RouteEntry {
+        match self {
+            AnyEntry::V1(entry) => entry,
+            AnyEntry::V2(entry) => entry,
+        }
+    }
+}
+
+impl RouteEntry for AnyEntry {
+    fn address_family_identifier(&self) -> address_family::Identifier {
+        self.as_route_entry().address_family_identifier()
+    }
+
+    fn destination(&self) -> Ipv4Addr {
+        self.as_route_entry().destination()
+    }
+
+    fn subnet_mask(&self) -> Option<Ipv4Addr> {
+        self.as_route_entry().subnet_mask()
+    }
+
+    fn next_hop(&self) -> Option<Ipv4Addr> {
+        self.as_route_entry().next_hop()
+    }
+
+    fn route_tag(&self) -> Option<RouteTag> {
+        self.as_route_entry().route_tag()
+    }
+
+    fn metric(&self) -> Metric {
+        self.as_route_entry().metric()
+    }
+}
+
+impl From<v1::Entry> for AnyEntry {
+    fn from(entry: v1::Entry) -> Self {
+        AnyEntry::V1(entry)
+    }
+}
+
+impl From<v2::Entry> for AnyEntry {
+    fn from(entry: v2::Entry) -> Self {
+        AnyEntry::V2(entry)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::entry::{AnyEntry, RouteEntry};
+    use crate::metric::Metric;
+    use crate::parser;
+    use crate::route_tag::RouteTag;
+    use std::net::Ipv4Addr;
+
+    fn reachable_destinations<'a>(
+        entries: impl Iterator<Item = &'a dyn RouteEntry>,
+    ) -> Vec<Ipv4Addr> {
+        entries
+            .filter(|entry| entry.metric().is_reachable())
+            .map(|entry| entry.destination())
+            .collect()
+    }
+
+    #[test]
+    fn test_entries_of_both_versions() {
+        let v1_packet = parser::parse(
+            vec![
+                2, 1, 0, 0, //
+                0, 2, 0, 0, //
+                192, 0, 2, 100, //
+                0, 0, 0, 0, //
+                0, 0, 0, 0, //
+                0, 0, 0, 1, //
+                0, 2, 0, 0, //
+                192, 0, 2, 101, //
+                0, 0, 0, 0, //
+                0, 0, 0, 0, //
+                0, 0, 0, 16, //
+            ]
+            .as_slice(),
+        )
+        .unwrap();
+        let v2_packet = parser::parse(
+            vec![
+                2, 2, 0, 0, //
+                0, 2, 1, 2, //
+                192, 0, 2, 0, //
+                255, 255, 255, 0, //
+                192, 0, 2, 111, //
+                0, 0, 0, 2, //
+            ]
+            .as_slice(),
+        )
+        .unwrap();
+
+        assert_eq!(
+            reachable_destinations(v1_packet.entries()),
+            vec![Ipv4Addr::new(192, 0, 2, 100)]
+        );
+        assert_eq!(
+            reachable_destinations(v2_packet.entries()),
+            vec![Ipv4Addr::new(192, 0, 2, 0)]
+        );
+
+        let v1_entry = v1_packet.entries().next().unwrap();
+        assert_eq!(v1_entry.subnet_mask(), None);
+        assert_eq!(v1_entry.next_hop(), None);
+        assert_eq!(v1_entry.route_tag(), None);
+
+        let entries = v2_packet.into_entries();
+        assert!(matches!(entries[0], AnyEntry::V2(_)));
+        assert_eq!(
+            entries[0].subnet_mask(),
+            Some(Ipv4Addr::new(255, 255, 255, 0))
+        );
+        assert_eq!(entries[0].next_hop(), Some(Ipv4Addr::new(192, 0, 2, 111)));
+        assert_eq!(entries[0].route_tag(), Some(RouteTag::new(258)));
+        assert_eq!(entries[0].metric(), Metric::from_raw(2));
+    }
+}
@@ -1,9 +1,11 @@
 use crate::parser::ParseError;
-use crate::parser::Parsed;
-use crate::serializer::{Serializable, SerializeError};
-use crate::{byte_reader, command, version, zero_bytes};
+use crate::parser::{Parsable, Parsed};
+use crate::serializer::{check_buffer_length, Serializable, SerializeError};
+use crate::{command, version, zero_bytes};
+use std::fmt;
 
-#[derive(PartialEq, Debug)]
+#[derive(PartialEq, Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Header {
     command: command::Kind,
     version: version::Version,
@@ -11,8 +13,7 @@ pub struct Header {
 
 pub fn parse(cursor: usize, bytes: &[u8]) -> Result<Parsed<Header>, ParseError> {
     let (command, cursor) = command::Kind::parse(cursor, bytes)?;
-    let (version_byte, cursor) = byte_reader::read(cursor, bytes)?;
-    let version_value = version::Version::from_u8(version_byte);
+    let (version_value, cursor) = version::Version::parse(cursor, bytes)?;
     let cursor = zero_bytes::skip(2, cursor, bytes)?;
     let header = Header::new(command, version_value);
 
@@ -33,19 +34,39 @@ impl Header {
     }
 }
 
+impl fmt::Display for Header {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.command, self.version)
+    }
+}
+
+impl Parsable for Header {
+    fn parse(cursor: usize, bytes: &[u8]) -> Result<Parsed<Header>, ParseError> {
+        parse(cursor, bytes)
+    }
+}
+
 impl Serializable for Header {
-    fn to_bytes(&self) -> Result<Vec<u8>, SerializeError> {
-        let command_bytes = self.get_command().to_bytes()?;
-        let version_bytes = self.get_version().to_bytes()?;
-        Ok([command_bytes, version_bytes, vec![0, 0]].concat())
+    fn serialized_len(&self) -> usize {
+        4
+    }
+
+    fn write_bytes(&self, buf: &mut [u8]) -> Result<usize, SerializeError> {
+        check_buffer_length(buf, self.serialized_len())?;
+
+        let mut cursor = self.get_command().write_bytes(buf)?;
+        cursor += self.get_version().write_bytes(&mut buf[cursor..])?;
+        buf[cursor..cursor + 2].fill(0);
+
+        Ok(cursor + 2)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use crate::header::Header;
-    use crate::parser::ParseError;
-    use crate::serializer::Serializable;
+    use crate::parser::{Parsable, ParseError};
+    use crate::serializer::{Serializable, SerializeError};
     use crate::{command, header, version};
 
     #[test]
@@ -58,6 +79,31 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_write_bytes() {
+        let header = Header::new(command::Kind::Response, version::Version::Version1);
+
+        let mut buf = [0xff; 5];
+        assert_eq!(header.write_bytes(&mut buf).unwrap(), 4);
+        assert_eq!(buf, [0x02, 0x01, 0x00, 0x00, 0xff]);
+
+        let mut buf = [0xff; 3];
+        assert_eq!(
+            header.write_bytes(&mut buf).unwrap_err(),
+            SerializeError::InsufficientBufferLength(4, 3)
+        );
+    }
+
+    #[test]
+    fn test_to_bytes_with_unknown_values() {
+        assert_eq!(
+            Header::new(command::Kind::Unknown(12), version::Version::Unknown(3))
+                .to_bytes()
+                .unwrap(),
+            vec![0x0c, 0x03, 0x00, 0x00]
+        );
+    }
+
     #[test]
     fn test_parse() {
         let (header, cursor) = header::parse(0, vec![0x01, 0x02, 0x00, 0x00].as_slice()).unwrap();
@@ -68,12 +114,23 @@ mod tests {
         assert_eq!(cursor, 4);
     }
 
+    #[test]
+    fn test_parsable() {
+        let bytes = vec![0xff, 0x02, 0x01, 0x00, 0x00];
+        let (header, cursor) = Header::parse(1, bytes.as_slice()).unwrap();
+        assert_eq!(
+            header,
+            Header::new(command::Kind::Response, version::Version::Version1)
+        );
+        assert_eq!(cursor, 5);
+    }
+
     #[test]
     fn test_parse_has_non_zero_byte() {
         let err = header::parse(0, vec![0x01, 0x02, 0x01, 0x00].as_slice()).unwrap_err();
-        assert_eq!(err, ParseError::NotZeroByte(0x01, 3));
+        assert_eq!(err, ParseError::NotZeroByte(0x01, 2));
 
         let err = header::parse(0, vec![0x01, 0x02, 0x00, 0x02].as_slice()).unwrap_err();
-        assert_eq!(err, ParseError::NotZeroByte(0x02, 4));
+        assert_eq!(err, ParseError::NotZeroByte(0x02, 3));
     }
 }
@@ -1,5 +1,5 @@
-use crate::parser::Parsed;
-use crate::serializer::SerializeError;
+use crate::parser::{Parsable, Parsed};
+use crate::serializer::{write_slice, Serializable, SerializeError};
 use crate::{byte_reader, parser::ParseError};
 use std::net::Ipv4Addr;
 
@@ -20,6 +20,22 @@ pub(crate) fn parse(cursor: usize, bytes: &[u8]) -> Result<Parsed<Ipv4Addr>, Par
     ))
 }
 
-pub(crate) fn to_bytes(ipv4: Ipv4Addr) -> Result<Vec<u8>, SerializeError> {
-    Ok(ipv4.octets().to_vec())
+pub(crate) fn write_bytes(ipv4: Ipv4Addr, buf: &mut [u8]) -> Result<usize, SerializeError> {
+    write_slice(&ipv4.octets(), buf)
+}
+
+impl Parsable for Ipv4Addr {
+    fn parse(cursor: usize, bytes: &[u8]) -> Result<Parsed<Ipv4Addr>, ParseError> {
+        parse(cursor, bytes)
+    }
+}
+
+impl Serializable for Ipv4Addr {
+    fn serialized_len(&self) -> usize {
+        4
+    }
+
+    fn write_bytes(&self, buf: &mut [u8]) -> Result<usize, SerializeError> {
+        write_bytes(*self, buf)
+    }
 }
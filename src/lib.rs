@@ -1,14 +1,29 @@
 pub mod address_family;
 mod byte_reader;
+#[cfg(feature = "tokio")]
+pub mod codec;
 pub mod command;
+pub mod diagnostic;
+pub mod dissector;
+pub mod encapsulation;
+pub mod entry;
 pub mod header;
 mod ipv4;
+pub mod lint;
+pub mod message;
 pub mod metric;
 pub mod packet;
 pub mod parser;
+#[cfg(feature = "pcap")]
+pub mod pcap;
+pub mod peek;
+pub mod prefix;
 pub mod route_tag;
 pub mod serializer;
+pub mod stream;
+pub mod text;
 pub mod v1;
 pub mod v2;
 pub mod version;
+pub mod view;
 mod zero_bytes;
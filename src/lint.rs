@@ -0,0 +1,528 @@
+use crate::metric::Metric;
+use crate::packet::Packet;
+use crate::prefix::is_contiguous_mask;
+use crate::{address_family, command, v1, v2};
+use std::fmt;
+use std::net::Ipv4Addr;
+
+#[derive(PartialEq, Eq, PartialOrd, Ord, Copy, Clone, Debug)]
+pub enum Severity {
+    Info,
+    Warning,
+    Error,
+}
+
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub enum Category {
+    /// The command is invalid, reserved or obsolete.
+    UnexpectedCommand,
+    /// The address family identifier isn't allowed at the position or for the command.
+    UnexpectedAddressFamily,
+    /// The metric is 0 or greater than 16.
+    MetricOutOfRange,
+    /// The subnet mask has non-contiguous bits.
+    NonContiguousSubnetMask,
+    /// The IP address has the host bits of the subnet mask set.
+    HostBitsSet,
+    /// The next hop is set in a request.
+    NextHopInRequest,
+    /// The destination must not be advertised, e.g. a loopback, multicast or class E address.
+    InvalidDestination,
+}
+
+/// Finding is a semantic problem of a packet that the linter found.
+#[derive(PartialEq, Clone, Debug)]
+pub struct Finding {
+    severity: Severity,
+    category: Category,
+    entry_index: Option<usize>,
+    message: String,
+}
+
+impl Finding {
+    fn new(
+        severity: Severity,
+        category: Category,
+        entry_index: Option<usize>,
+        message: String,
+    ) -> Self {
+        Finding {
+            severity,
+            category,
+            entry_index,
+            message,
+        }
+    }
+
+    pub fn get_severity(&self) -> Severity {
+        self.severity
+    }
+
+    pub fn get_category(&self) -> Category {
+        self.category
+    }
+
+    /// get_entry_index returns the index of the entry that the finding is about; `None` means the header.
+    pub fn get_entry_index(&self) -> Option<usize> {
+        self.entry_index
+    }
+
+    pub fn get_message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl fmt::Display for Finding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let severity = match self.severity {
+            Severity::Info => "info",
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        };
+        match self.entry_index {
+            Some(i) => write!(f, "[{}] entry[{}]: {}", severity, i, self.message),
+            None => write!(f, "[{}] header: {}", severity, self.message),
+        }
+    }
+}
+
+/// lint_v1 checks the RIPv1 packet against RFC1058 and returns the findings.
+pub fn lint_v1(packet: &Packet<v1::Entry>) -> Vec<Finding> {
+    let command = packet.get_header().get_command();
+    let mut findings = lint_command(command);
+
+    let entries = packet.get_entries();
+    if is_whole_table_request(command, entries.len(), |i| {
+        (
+            entries[i].get_address_family_identifier(),
+            entries[i].get_metric(),
+        )
+    }) {
+        return findings;
+    }
+
+    for (i, entry) in entries.iter().enumerate() {
+        match entry.get_address_family_identifier() {
+            address_family::Identifier::IP => {}
+            address_family::Identifier::AuthenticationPresent => findings.push(Finding::new(
+                Severity::Error,
+                Category::UnexpectedAddressFamily,
+                Some(i),
+                "RIPv1 doesn't support the authentication entry".to_string(),
+            )),
+            afi => findings.push(unexpected_address_family(command, i, afi)),
+        }
+
+        if command == command::Kind::Response {
+            findings.extend(lint_metric(i, entry.get_metric()));
+            findings.extend(lint_destination(i, entry.get_ip_address()));
+        }
+    }
+
+    findings
+}
+
+/// lint_v2 checks the RIPv2 packet against RFC2453 and returns the findings.
+pub fn lint_v2(packet: &Packet<v2::Entry>) -> Vec<Finding> {
+    let command = packet.get_header().get_command();
+    let mut findings = lint_command(command);
+
+    let entries = packet.get_entries();
+    if is_whole_table_request(command, entries.len(), |i| {
+        (
+            entries[i].get_address_family_identifier(),
+            entries[i].get_metric(),
+        )
+    }) {
+        return findings;
+    }
+
+    for (i, entry) in entries.iter().enumerate() {
+        match entry.get_address_family_identifier() {
+            address_family::Identifier::IP => {}
+            address_family::Identifier::AuthenticationPresent => {
+                if i != 0 {
+                    findings.push(Finding::new(
+                        Severity::Error,
+                        Category::UnexpectedAddressFamily,
+                        Some(i),
+                        "the authentication entry must be the first entry".to_string(),
+                    ));
+                }
+                continue;
+            }
+            afi => findings.push(unexpected_address_family(command, i, afi)),
+        }
+
+        let mask = u32::from(entry.get_subnet_mask());
+        if !is_contiguous_mask(entry.get_subnet_mask()) {
+            findings.push(Finding::new(
+                Severity::Error,
+                Category::NonContiguousSubnetMask,
+                Some(i),
+                format!("subnet mask {} is not contiguous", entry.get_subnet_mask()),
+            ));
+        } else if mask != 0 && u32::from(entry.get_ip_address()) & !mask != 0 {
+            findings.push(Finding::new(
+                Severity::Warning,
+                Category::HostBitsSet,
+                Some(i),
+                format!(
+                    "IP address {} has the host bits of subnet mask {} set",
+                    entry.get_ip_address(),
+                    entry.get_subnet_mask()
+                ),
+            ));
+        }
+
+        match command {
+            command::Kind::Request if !entry.get_next_hop().is_unspecified() => {
+                findings.push(Finding::new(
+                    Severity::Warning,
+                    Category::NextHopInRequest,
+                    Some(i),
+                    format!("next hop {} is set in the request", entry.get_next_hop()),
+                ));
+            }
+            command::Kind::Response => {
+                findings.extend(lint_metric(i, entry.get_metric()));
+                findings.extend(lint_destination(i, entry.get_ip_address()));
+            }
+            _ => {}
+        }
+    }
+
+    findings
+}
+
+fn lint_command(command: command::Kind) -> Vec<Finding> {
+    match command {
+        command::Kind::Request | command::Kind::Response => vec![],
+        command::Kind::TraceOn | command::Kind::TraceOff => vec![Finding::new(
+            Severity::Warning,
+            Category::UnexpectedCommand,
+            None,
+            format!("command {:?} is obsolete", command),
+        )],
+        command::Kind::Invalid | command::Kind::Reserved | command::Kind::Unknown(_) => {
+            vec![Finding::new(
+                Severity::Error,
+                Category::UnexpectedCommand,
+                None,
+                format!("command {:?} must not be used", command),
+            )]
+        }
+        _ => vec![Finding::new(
+            Severity::Info,
+            Category::UnexpectedCommand,
+            None,
+            format!(
+                "command {:?} is only valid on demand circuits (RFC1582, RFC2091)",
+                command
+            ),
+        )],
+    }
+}
+
+/// is_whole_table_request returns whether the packet is a request for the whole routing table; that is
+/// a request which has exactly one entry with the unspecified address family and the infinity metric.
+fn is_whole_table_request(
+    command: command::Kind,
+    num_of_entries: usize,
+    first_entry: impl Fn(usize) -> (address_family::Identifier, Metric),
+) -> bool {
+    command == command::Kind::Request
+        && num_of_entries == 1
+        && first_entry(0) == (address_family::Identifier::Unspecified, Metric::INFINITY)
+}
+
+fn unexpected_address_family(
+    command: command::Kind,
+    entry_index: usize,
+    afi: address_family::Identifier,
+) -> Finding {
+    Finding::new(
+        Severity::Error,
+        Category::UnexpectedAddressFamily,
+        Some(entry_index),
+        format!(
+            "address family identifier {:?} is not allowed in {:?}",
+            afi, command
+        ),
+    )
+}
+
+fn lint_metric(entry_index: usize, metric: Metric) -> Option<Finding> {
+    if !metric.is_valid() {
+        return Some(Finding::new(
+            Severity::Error,
+            Category::MetricOutOfRange,
+            Some(entry_index),
+            format!("metric {} is out of the range 1..=16", metric),
+        ));
+    }
+    None
+}
+
+fn lint_destination(entry_index: usize, ip_address: Ipv4Addr) -> Option<Finding> {
+    let reason = if ip_address.is_loopback() {
+        "a loopback address"
+    } else if ip_address.is_multicast() {
+        "a multicast address"
+    } else if ip_address.is_broadcast() || ip_address.octets()[0] >= 240 {
+        "a class E address"
+    } else if ip_address.octets()[0] == 0 && !ip_address.is_unspecified() {
+        "in the network 0.0.0.0/8"
+    } else {
+        return None;
+    };
+
+    Some(Finding::new(
+        Severity::Warning,
+        Category::InvalidDestination,
+        Some(entry_index),
+        format!("destination {} is {}", ip_address, reason),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::header::Header;
+    use crate::lint::{lint_v1, lint_v2, Category, Severity};
+    use crate::metric::Metric;
+    use crate::packet::Packet;
+    use crate::route_tag::RouteTag;
+    use crate::{address_family, command, v1, v2, version};
+    use std::net::Ipv4Addr;
+
+    fn v2_entry(ip_address: Ipv4Addr, subnet_mask: Ipv4Addr, metric: u32) -> v2::Entry {
+        v2::Entry::new(
+            address_family::Identifier::IP,
+            RouteTag::new(0),
+            ip_address,
+            subnet_mask,
+            Ipv4Addr::new(0, 0, 0, 0),
+            Metric::from_raw(metric),
+        )
+    }
+
+    #[test]
+    fn test_lint_valid_v2_response() {
+        let packet = Packet::make_v2_packet(
+            Header::new(command::Kind::Response, version::Version::Version2),
+            vec![
+                v2_entry(
+                    Ipv4Addr::new(192, 0, 2, 0),
+                    Ipv4Addr::new(255, 255, 255, 0),
+                    1,
+                ),
+                v2_entry(Ipv4Addr::new(0, 0, 0, 0), Ipv4Addr::new(0, 0, 0, 0), 16),
+            ],
+        )
+        .unwrap();
+
+        assert_eq!(lint_v2(&packet), vec![]);
+    }
+
+    #[test]
+    fn test_lint_v2_response() {
+        let packet = Packet::make_v2_packet(
+            Header::new(command::Kind::Response, version::Version::Version2),
+            vec![
+                v2_entry(
+                    Ipv4Addr::new(192, 0, 2, 0),
+                    Ipv4Addr::new(255, 0, 255, 0),
+                    1,
+                ),
+                v2_entry(
+                    Ipv4Addr::new(192, 0, 2, 1),
+                    Ipv4Addr::new(255, 255, 255, 0),
+                    1,
+                ),
+                v2_entry(
+                    Ipv4Addr::new(192, 0, 2, 0),
+                    Ipv4Addr::new(255, 255, 255, 0),
+                    0,
+                ),
+                v2_entry(
+                    Ipv4Addr::new(192, 0, 2, 0),
+                    Ipv4Addr::new(255, 255, 255, 0),
+                    67305985,
+                ),
+                v2::Entry::new(
+                    address_family::Identifier::Unspecified,
+                    RouteTag::new(0),
+                    Ipv4Addr::new(192, 0, 2, 0),
+                    Ipv4Addr::new(255, 255, 255, 0),
+                    Ipv4Addr::new(0, 0, 0, 0),
+                    Metric::from_raw(1),
+                ),
+                v2_entry(
+                    Ipv4Addr::new(224, 0, 0, 9),
+                    Ipv4Addr::new(255, 255, 255, 255),
+                    1,
+                ),
+            ],
+        )
+        .unwrap();
+
+        let findings = lint_v2(&packet);
+        assert_eq!(
+            findings
+                .iter()
+                .map(|f| (f.get_entry_index(), f.get_category(), f.get_severity()))
+                .collect::<Vec<_>>(),
+            vec![
+                (Some(0), Category::NonContiguousSubnetMask, Severity::Error),
+                (Some(1), Category::HostBitsSet, Severity::Warning),
+                (Some(2), Category::MetricOutOfRange, Severity::Error),
+                (Some(3), Category::MetricOutOfRange, Severity::Error),
+                (Some(4), Category::UnexpectedAddressFamily, Severity::Error),
+                (Some(5), Category::InvalidDestination, Severity::Warning),
+            ]
+        );
+        assert_eq!(
+            findings[1].to_string(),
+            "[warning] entry[1]: IP address 192.0.2.1 has the host bits of subnet mask 255.255.255.0 set"
+        );
+    }
+
+    #[test]
+    fn test_lint_v2_request() {
+        let whole_table_request = Packet::make_v2_packet(
+            Header::new(command::Kind::Request, version::Version::Version2),
+            vec![v2::Entry::new(
+                address_family::Identifier::Unspecified,
+                RouteTag::new(0),
+                Ipv4Addr::new(0, 0, 0, 0),
+                Ipv4Addr::new(0, 0, 0, 0),
+                Ipv4Addr::new(0, 0, 0, 0),
+                Metric::from_raw(16),
+            )],
+        )
+        .unwrap();
+        assert_eq!(lint_v2(&whole_table_request), vec![]);
+
+        let packet = Packet::make_v2_packet(
+            Header::new(command::Kind::Request, version::Version::Version2),
+            vec![v2::Entry::new(
+                address_family::Identifier::IP,
+                RouteTag::new(0),
+                Ipv4Addr::new(192, 0, 2, 0),
+                Ipv4Addr::new(255, 255, 255, 0),
+                Ipv4Addr::new(192, 0, 2, 1),
+                Metric::from_raw(0),
+            )],
+        )
+        .unwrap();
+        let findings = lint_v2(&packet);
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].get_category(), Category::NextHopInRequest);
+    }
+
+    #[test]
+    fn test_lint_v2_authentication_entry() {
+        let auth_entry = v2::Entry::new(
+            address_family::Identifier::AuthenticationPresent,
+            RouteTag::new(2),
+            Ipv4Addr::new(0, 0, 0, 0),
+            Ipv4Addr::new(0, 0, 0, 0),
+            Ipv4Addr::new(0, 0, 0, 0),
+            Metric::from_raw(0),
+        );
+        let route = v2_entry(
+            Ipv4Addr::new(192, 0, 2, 0),
+            Ipv4Addr::new(255, 255, 255, 0),
+            1,
+        );
+
+        let packet = Packet::make_v2_packet(
+            Header::new(command::Kind::Response, version::Version::Version2),
+            vec![auth_entry, route],
+        )
+        .unwrap();
+        assert_eq!(lint_v2(&packet), vec![]);
+
+        let auth_entry = v2::Entry::new(
+            address_family::Identifier::AuthenticationPresent,
+            RouteTag::new(2),
+            Ipv4Addr::new(0, 0, 0, 0),
+            Ipv4Addr::new(0, 0, 0, 0),
+            Ipv4Addr::new(0, 0, 0, 0),
+            Metric::from_raw(0),
+        );
+        let route = v2_entry(
+            Ipv4Addr::new(192, 0, 2, 0),
+            Ipv4Addr::new(255, 255, 255, 0),
+            1,
+        );
+        let packet = Packet::make_v2_packet(
+            Header::new(command::Kind::Response, version::Version::Version2),
+            vec![route, auth_entry],
+        )
+        .unwrap();
+        let findings = lint_v2(&packet);
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].get_entry_index(), Some(1));
+        assert_eq!(
+            findings[0].get_category(),
+            Category::UnexpectedAddressFamily
+        );
+    }
+
+    #[test]
+    fn test_lint_v1() {
+        let packet = Packet::make_v1_packet(
+            Header::new(command::Kind::Response, version::Version::Version1),
+            vec![
+                v1::Entry::new(
+                    address_family::Identifier::IP,
+                    Ipv4Addr::new(192, 0, 2, 0),
+                    Metric::from_raw(1),
+                ),
+                v1::Entry::new(
+                    address_family::Identifier::Unspecified,
+                    Ipv4Addr::new(192, 0, 2, 0),
+                    Metric::from_raw(1),
+                ),
+                v1::Entry::new(
+                    address_family::Identifier::IP,
+                    Ipv4Addr::new(127, 0, 0, 1),
+                    Metric::from_raw(17),
+                ),
+            ],
+        )
+        .unwrap();
+
+        let findings = lint_v1(&packet);
+        assert_eq!(
+            findings
+                .iter()
+                .map(|f| (f.get_entry_index(), f.get_category()))
+                .collect::<Vec<_>>(),
+            vec![
+                (Some(1), Category::UnexpectedAddressFamily),
+                (Some(2), Category::MetricOutOfRange),
+                (Some(2), Category::InvalidDestination),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_lint_command() {
+        let packet = Packet::make_v1_packet(
+            Header::new(command::Kind::TraceOn, version::Version::Version1),
+            vec![],
+        )
+        .unwrap();
+
+        let findings = lint_v1(&packet);
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].get_entry_index(), None);
+        assert_eq!(findings[0].get_severity(), Severity::Warning);
+        assert_eq!(
+            findings[0].to_string(),
+            "[warning] header: command TraceOn is obsolete"
+        );
+    }
+}
@@ -0,0 +1,651 @@
+use crate::entry::{AnyEntry, RouteEntry};
+use crate::header::Header;
+use crate::packet::MAX_ENTRIES_NUM;
+use crate::parser::{
+    check_entries_length, parse_command_entries, PacketParsable, Parsable, ParseError,
+    ParseOptions, Parsed,
+};
+use crate::serializer::{check_buffer_length, write_slice, Serializable, SerializeError};
+use crate::{byte_reader, command, v1, v2, version};
+use std::fmt;
+
+/// The version of the update header that RFC2091 defines.
+pub const UPDATE_HEADER_VERSION: u8 = 1;
+/// The length of the update header in bytes.
+pub const UPDATE_HEADER_LENGTH: usize = 4;
+
+/// UpdateHeader is the header that follows the RIP header in the triggered RIP messages (RFC2091).
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct UpdateHeader {
+    version: u8,
+    flush: bool,
+    sequence_number: u16,
+}
+
+impl UpdateHeader {
+    pub fn new(flush: bool, sequence_number: u16) -> Self {
+        UpdateHeader {
+            version: UPDATE_HEADER_VERSION,
+            flush,
+            sequence_number,
+        }
+    }
+
+    pub fn get_version(&self) -> u8 {
+        self.version
+    }
+
+    pub fn is_flush(&self) -> bool {
+        self.flush
+    }
+
+    pub fn get_sequence_number(&self) -> u16 {
+        self.sequence_number
+    }
+}
+
+impl Parsable for UpdateHeader {
+    fn parse(cursor: usize, bytes: &[u8]) -> Result<Parsed<UpdateHeader>, ParseError> {
+        let (version, version_cursor) = byte_reader::read(cursor, bytes)?;
+        if version != UPDATE_HEADER_VERSION {
+            return Err(ParseError::UnsupportedUpdateHeaderVersion(version, cursor));
+        }
+        let cursor = version_cursor;
+        let (flush, cursor) = byte_reader::read(cursor, bytes)?;
+        let (sequence_number_first_byte, cursor) = byte_reader::read(cursor, bytes)?;
+        let (sequence_number_second_byte, cursor) = byte_reader::read(cursor, bytes)?;
+
+        Ok((
+            UpdateHeader {
+                version,
+                flush: flush != 0,
+                sequence_number: ((sequence_number_first_byte as u16) << 8)
+                    + sequence_number_second_byte as u16,
+            },
+            cursor,
+        ))
+    }
+}
+
+impl Serializable for UpdateHeader {
+    fn serialized_len(&self) -> usize {
+        UPDATE_HEADER_LENGTH
+    }
+
+    fn write_bytes(&self, buf: &mut [u8]) -> Result<usize, SerializeError> {
+        let sequence_number = self.sequence_number.to_be_bytes();
+        write_slice(
+            &[
+                self.version,
+                self.flush as u8,
+                sequence_number[0],
+                sequence_number[1],
+            ],
+            buf,
+        )
+    }
+}
+
+/// RipMessage is a RIP message whose payload is typed by the command.
+/// It can't be serialized with more than MAX_ENTRIES_NUM entries.
+#[derive(PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum RipMessage<T> {
+    Request(Vec<T>),
+    Response(Vec<T>),
+    /// TraceOn carries the name of the trace file (RFC1058; obsolete).
+    TraceOn(Vec<u8>),
+    TraceOff,
+    TriggeredRequest(UpdateHeader, Vec<T>),
+    TriggeredResponse(UpdateHeader, Vec<T>),
+    TriggeredAck(UpdateHeader),
+    UpdateRequest(UpdateHeader, Vec<T>),
+    UpdateResponse(UpdateHeader, Vec<T>),
+    UpdateAck(UpdateHeader),
+}
+
+#[derive(PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ParsedMessage {
+    V1(RipMessage<v1::Entry>),
+    V2(RipMessage<v2::Entry>),
+}
+
+impl<T> RipMessage<T> {
+    pub fn get_command(&self) -> command::Kind {
+        match self {
+            RipMessage::Request(_) => command::Kind::Request,
+            RipMessage::Response(_) => command::Kind::Response,
+            RipMessage::TraceOn(_) => command::Kind::TraceOn,
+            RipMessage::TraceOff => command::Kind::TraceOff,
+            RipMessage::TriggeredRequest(_, _) => command::Kind::TriggeredRequest,
+            RipMessage::TriggeredResponse(_, _) => command::Kind::TriggeredResponse,
+            RipMessage::TriggeredAck(_) => command::Kind::TriggeredAcknowledgement,
+            RipMessage::UpdateRequest(_, _) => command::Kind::UpdateRequest,
+            RipMessage::UpdateResponse(_, _) => command::Kind::UpdateResponse,
+            RipMessage::UpdateAck(_) => command::Kind::UpdateAcknowledge,
+        }
+    }
+
+    pub fn get_update_header(&self) -> Option<&UpdateHeader> {
+        match self {
+            RipMessage::TriggeredRequest(update_header, _)
+            | RipMessage::TriggeredResponse(update_header, _)
+            | RipMessage::TriggeredAck(update_header)
+            | RipMessage::UpdateRequest(update_header, _)
+            | RipMessage::UpdateResponse(update_header, _)
+            | RipMessage::UpdateAck(update_header) => Some(update_header),
+            _ => None,
+        }
+    }
+
+    /// get_entries returns the RIP entries; it is empty for the commands that don't carry any entry.
+    pub fn get_entries(&self) -> &[T] {
+        match self {
+            RipMessage::Request(entries)
+            | RipMessage::Response(entries)
+            | RipMessage::TriggeredRequest(_, entries)
+            | RipMessage::TriggeredResponse(_, entries)
+            | RipMessage::UpdateRequest(_, entries)
+            | RipMessage::UpdateResponse(_, entries) => entries,
+            _ => &[],
+        }
+    }
+
+    pub fn into_entries(self) -> Vec<T> {
+        match self {
+            RipMessage::Request(entries)
+            | RipMessage::Response(entries)
+            | RipMessage::TriggeredRequest(_, entries)
+            | RipMessage::TriggeredResponse(_, entries)
+            | RipMessage::UpdateRequest(_, entries)
+            | RipMessage::UpdateResponse(_, entries) => entries,
+            _ => vec![],
+        }
+    }
+}
+
+impl ParsedMessage {
+    pub fn get_version(&self) -> version::Version {
+        match self {
+            ParsedMessage::V1(_) => version::Version::Version1,
+            ParsedMessage::V2(_) => version::Version::Version2,
+        }
+    }
+
+    pub fn get_command(&self) -> command::Kind {
+        match self {
+            ParsedMessage::V1(message) => message.get_command(),
+            ParsedMessage::V2(message) => message.get_command(),
+        }
+    }
+
+    pub fn get_update_header(&self) -> Option<&UpdateHeader> {
+        match self {
+            ParsedMessage::V1(message) => message.get_update_header(),
+            ParsedMessage::V2(message) => message.get_update_header(),
+        }
+    }
+
+    /// entries returns the entries of the message regardless of the version.
+    pub fn entries(&self) -> impl Iterator<Item = &dyn RouteEntry> + '_ {
+        let (v1_entries, v2_entries): (&[v1::Entry], &[v2::Entry]) = match self {
+            ParsedMessage::V1(message) => (message.get_entries(), &[]),
+            ParsedMessage::V2(message) => (&[], message.get_entries()),
+        };
+        v1_entries
+            .iter()
+            .map(|entry| entry as &dyn RouteEntry)
+            .chain(v2_entries.iter().map(|entry| entry as &dyn RouteEntry))
+    }
+
+    pub fn into_entries(self) -> Vec<AnyEntry> {
+        match self {
+            ParsedMessage::V1(message) => message
+                .into_entries()
+                .into_iter()
+                .map(AnyEntry::V1)
+                .collect(),
+            ParsedMessage::V2(message) => message
+                .into_entries()
+                .into_iter()
+                .map(AnyEntry::V2)
+                .collect(),
+        }
+    }
+}
+
+/// The message is formatted in the same way as the packet, e.g. `response v2 { 10.0.0.0/8 via 0.0.0.0 metric 2; }`.
+/// The update header follows the version as `sequence 7 flush`, and the file name of TraceOn as `file "/tmp/trace"`.
+impl fmt::Display for ParsedMessage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParsedMessage::V1(message) => fmt_message(message, version::Version::Version1, f),
+            ParsedMessage::V2(message) => fmt_message(message, version::Version::Version2, f),
+        }
+    }
+}
+
+fn fmt_message<T: fmt::Display>(
+    message: &RipMessage<T>,
+    version: version::Version,
+    f: &mut fmt::Formatter<'_>,
+) -> fmt::Result {
+    write!(f, "{}", Header::new(message.get_command(), version))?;
+    if let Some(update_header) = message.get_update_header() {
+        write!(f, " sequence {}", update_header.get_sequence_number())?;
+        if update_header.is_flush() {
+            write!(f, " flush")?;
+        }
+    }
+    if let RipMessage::TraceOn(file_name) = message {
+        write!(f, " file {:?}", String::from_utf8_lossy(file_name))?;
+    }
+
+    write!(f, " {{")?;
+    let entries = message.get_entries();
+    for entry in entries {
+        if f.alternate() {
+            write!(f, "\n    {};", entry)?;
+        } else {
+            write!(f, " {};", entry)?;
+        }
+    }
+    if f.alternate() && !entries.is_empty() {
+        write!(f, "\n}}")
+    } else {
+        write!(f, " }}")
+    }
+}
+
+/// parse_body parses the payload that follows the RIP header according to the command.
+/// The triggered commands of RFC1582 share the layout of the update header with RFC2091.
+pub(crate) fn parse_body<T>(
+    command: command::Kind,
+    parser: &dyn PacketParsable<T>,
+    cursor: usize,
+    bytes: &[u8],
+    options: &ParseOptions,
+) -> Result<(RipMessage<T>, usize), ParseError> {
+    let entries = |cursor| parse_message_entries(command, parser, cursor, bytes, options);
+
+    match command {
+        command::Kind::Request => entries(cursor).map(|(e, end)| (RipMessage::Request(e), end)),
+        command::Kind::Response => entries(cursor).map(|(e, end)| (RipMessage::Response(e), end)),
+        command::Kind::TraceOn => Ok((RipMessage::TraceOn(bytes[cursor..].to_vec()), bytes.len())),
+        command::Kind::TraceOff => {
+            expect_no_payload(cursor, bytes)?;
+            Ok((RipMessage::TraceOff, cursor))
+        }
+        command::Kind::TriggeredRequest => {
+            let (update_header, cursor) = UpdateHeader::parse(cursor, bytes)?;
+            entries(cursor).map(|(e, end)| (RipMessage::TriggeredRequest(update_header, e), end))
+        }
+        command::Kind::TriggeredResponse => {
+            let (update_header, cursor) = UpdateHeader::parse(cursor, bytes)?;
+            entries(cursor).map(|(e, end)| (RipMessage::TriggeredResponse(update_header, e), end))
+        }
+        command::Kind::TriggeredAcknowledgement => {
+            let (update_header, cursor) = UpdateHeader::parse(cursor, bytes)?;
+            expect_no_payload(cursor, bytes)?;
+            Ok((RipMessage::TriggeredAck(update_header), cursor))
+        }
+        command::Kind::UpdateRequest => {
+            let (update_header, cursor) = UpdateHeader::parse(cursor, bytes)?;
+            entries(cursor).map(|(e, end)| (RipMessage::UpdateRequest(update_header, e), end))
+        }
+        command::Kind::UpdateResponse => {
+            let (update_header, cursor) = UpdateHeader::parse(cursor, bytes)?;
+            entries(cursor).map(|(e, end)| (RipMessage::UpdateResponse(update_header, e), end))
+        }
+        command::Kind::UpdateAcknowledge => {
+            let (update_header, cursor) = UpdateHeader::parse(cursor, bytes)?;
+            expect_no_payload(cursor, bytes)?;
+            Ok((RipMessage::UpdateAck(update_header), cursor))
+        }
+        _ => Err(ParseError::UnsupportedCommandKind(command.to_u8(), 0)),
+    }
+}
+
+/// parse_message_entries parses the RIP entries from the cursor up to the last whole entry.
+/// It returns the entries with the end of the last entry; the bytes after that are treated by the trailing bytes policy.
+fn parse_message_entries<T>(
+    command: command::Kind,
+    parser: &dyn PacketParsable<T>,
+    cursor: usize,
+    bytes: &[u8],
+    options: &ParseOptions,
+) -> Result<(Vec<T>, usize), ParseError> {
+    let end = check_entries_length(cursor, bytes, options)?;
+    let entries = parse_command_entries(command, parser, cursor, &bytes[..end])?;
+    Ok((entries, end))
+}
+
+fn expect_no_payload(cursor: usize, bytes: &[u8]) -> Result<(), ParseError> {
+    if cursor < bytes.len() {
+        return Err(ParseError::UnexpectedPayload(cursor));
+    }
+    Ok(())
+}
+
+impl<T: Serializable> RipMessage<T> {
+    fn body_len(&self) -> usize {
+        let update_header_len = self.get_update_header().map_or(0, |h| h.serialized_len());
+        let payload_len = match self {
+            RipMessage::TraceOn(file_name) => file_name.len(),
+            _ => self.get_entries().iter().map(|e| e.serialized_len()).sum(),
+        };
+        update_header_len + payload_len
+    }
+
+    fn write_message(
+        &self,
+        version: version::Version,
+        buf: &mut [u8],
+    ) -> Result<usize, SerializeError> {
+        let num_of_entries = self.get_entries().len();
+        if num_of_entries > MAX_ENTRIES_NUM {
+            return Err(SerializeError::MaxRIPEntriesNumberExceeded(
+                num_of_entries,
+                MAX_ENTRIES_NUM,
+            ));
+        }
+        check_buffer_length(buf, 4 + self.body_len())?;
+
+        let mut cursor = Header::new(self.get_command(), version).write_bytes(buf)?;
+        if let Some(update_header) = self.get_update_header() {
+            cursor += update_header.write_bytes(&mut buf[cursor..])?;
+        }
+        if let RipMessage::TraceOn(file_name) = self {
+            cursor += write_slice(file_name, &mut buf[cursor..])?;
+        }
+        for entry in self.get_entries() {
+            cursor += entry.write_bytes(&mut buf[cursor..])?;
+        }
+
+        Ok(cursor)
+    }
+}
+
+impl Serializable for RipMessage<v1::Entry> {
+    fn serialized_len(&self) -> usize {
+        4 + self.body_len()
+    }
+
+    fn write_bytes(&self, buf: &mut [u8]) -> Result<usize, SerializeError> {
+        self.write_message(version::Version::Version1, buf)
+    }
+}
+
+impl Serializable for RipMessage<v2::Entry> {
+    fn serialized_len(&self) -> usize {
+        4 + self.body_len()
+    }
+
+    fn write_bytes(&self, buf: &mut [u8]) -> Result<usize, SerializeError> {
+        self.write_message(version::Version::Version2, buf)
+    }
+}
+
+impl Serializable for ParsedMessage {
+    fn serialized_len(&self) -> usize {
+        match self {
+            ParsedMessage::V1(message) => message.serialized_len(),
+            ParsedMessage::V2(message) => message.serialized_len(),
+        }
+    }
+
+    fn write_bytes(&self, buf: &mut [u8]) -> Result<usize, SerializeError> {
+        match self {
+            ParsedMessage::V1(message) => message.write_bytes(buf),
+            ParsedMessage::V2(message) => message.write_bytes(buf),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::address_family::Identifier;
+    use crate::message::{ParsedMessage, RipMessage, UpdateHeader};
+    use crate::metric::Metric;
+    use crate::parser::{self, ParseError};
+    use crate::route_tag::RouteTag;
+    use crate::serializer::{Serializable, SerializeError};
+    use crate::{command, v1, v2};
+    use std::net::Ipv4Addr;
+
+    #[test]
+    fn test_parse_and_serialize_v2_response() {
+        let bytes = vec![
+            2, 2, 0, 0, //
+            0, 2, 1, 2, //
+            192, 0, 2, 100, //
+            255, 255, 255, 0, //
+            192, 0, 2, 111, //
+            0, 0, 0, 1, //
+        ];
+        let message = parser::parse(bytes.as_slice()).unwrap();
+        assert_eq!(
+            message,
+            ParsedMessage::V2(RipMessage::Response(vec![v2::Entry::new(
+                Identifier::IP,
+                RouteTag::new(258),
+                Ipv4Addr::new(192, 0, 2, 100),
+                Ipv4Addr::new(255, 255, 255, 0),
+                Ipv4Addr::new(192, 0, 2, 111),
+                Metric::from_raw(1),
+            )]))
+        );
+        assert_eq!(message.to_bytes().unwrap(), bytes);
+    }
+
+    #[test]
+    fn test_parse_and_serialize_update_messages() {
+        let bytes = vec![
+            9, 1, 0, 0, //
+            1, 1, 0x12, 0x34, // update header
+            0, 2, 0, 0, //
+            192, 0, 2, 100, //
+            0, 0, 0, 0, //
+            0, 0, 0, 0, //
+            0, 0, 0, 16, //
+        ];
+        let message = parser::parse(bytes.as_slice()).unwrap();
+        let update_header = UpdateHeader::new(true, 0x1234);
+        assert_eq!(
+            message,
+            ParsedMessage::V1(RipMessage::UpdateRequest(
+                update_header,
+                vec![v1::Entry::new(
+                    Identifier::IP,
+                    Ipv4Addr::new(192, 0, 2, 100),
+                    Metric::INFINITY,
+                )]
+            ))
+        );
+        assert_eq!(message.to_bytes().unwrap(), bytes);
+
+        let bytes = vec![
+            11, 2, 0, 0, //
+            1, 0, 0, 7, // update header
+        ];
+        let message = parser::parse(bytes.as_slice()).unwrap();
+        match &message {
+            ParsedMessage::V2(message) => {
+                assert_eq!(message.get_command(), command::Kind::UpdateAcknowledge);
+                assert_eq!(
+                    message.get_update_header().unwrap().get_sequence_number(),
+                    7
+                );
+                assert!(message.get_entries().is_empty());
+            }
+            _ => panic!("unexpected version"),
+        }
+        assert_eq!(message.to_bytes().unwrap(), bytes);
+
+        assert_eq!(
+            parser::parse(vec![11, 2, 0, 0, 1, 0, 0, 7, 0].as_slice()).unwrap_err(),
+            ParseError::UnexpectedPayload(8)
+        );
+    }
+
+    #[test]
+    fn test_parse_message_with_truncated_entry() {
+        let entry = [
+            0, 2, 0, 0, //
+            192, 0, 2, 0, //
+            255, 255, 255, 0, //
+            0, 0, 0, 0, //
+            0, 0, 0, 1, //
+        ];
+
+        let mut bytes = vec![2, 2, 0, 0];
+        bytes.extend(entry);
+        bytes.extend(&entry[..3]);
+        assert_eq!(
+            parser::parse(bytes.as_slice()).unwrap_err(),
+            ParseError::TrailingBytes(3, 24)
+        );
+
+        let mut bytes = vec![10, 2, 0, 0, 1, 0, 0, 7];
+        bytes.extend(entry);
+        bytes.extend(&entry[..3]);
+        assert_eq!(
+            parser::parse(bytes.as_slice()).unwrap_err(),
+            ParseError::TrailingBytes(3, 28)
+        );
+    }
+
+    #[test]
+    fn test_parse_trace_messages() {
+        let bytes = vec![3, 1, 0, 0, b'/', b't', b'm', b'p'];
+        let message = parser::parse(bytes.as_slice()).unwrap();
+        assert_eq!(
+            message,
+            ParsedMessage::V1(RipMessage::TraceOn(b"/tmp".to_vec()))
+        );
+        assert_eq!(message.to_bytes().unwrap(), bytes);
+
+        assert_eq!(
+            parser::parse(vec![4, 2, 0, 0].as_slice()).unwrap(),
+            ParsedMessage::V2(RipMessage::TraceOff)
+        );
+        assert_eq!(
+            parser::parse(vec![4, 2, 0, 0, 1].as_slice()).unwrap_err(),
+            ParseError::UnexpectedPayload(4)
+        );
+    }
+
+    #[test]
+    fn test_parse_and_serialize_triggered_messages() {
+        let bytes = vec![6, 1, 0, 0, 1, 1, 0, 1];
+        let message = parser::parse(bytes.as_slice()).unwrap();
+        assert_eq!(
+            message,
+            ParsedMessage::V1(RipMessage::TriggeredRequest(
+                UpdateHeader::new(true, 1),
+                vec![]
+            ))
+        );
+        assert_eq!(message.to_bytes().unwrap(), bytes);
+
+        let bytes = vec![
+            7, 2, 0, 0, //
+            1, 0, 0, 2, // update header
+            0, 2, 0, 0, //
+            192, 0, 2, 0, //
+            255, 255, 255, 0, //
+            0, 0, 0, 0, //
+            0, 0, 0, 3, //
+        ];
+        let message = parser::parse(bytes.as_slice()).unwrap();
+        assert_eq!(
+            message,
+            ParsedMessage::V2(RipMessage::TriggeredResponse(
+                UpdateHeader::new(false, 2),
+                vec![v2::Entry::new(
+                    Identifier::IP,
+                    RouteTag::new(0),
+                    Ipv4Addr::new(192, 0, 2, 0),
+                    Ipv4Addr::new(255, 255, 255, 0),
+                    Ipv4Addr::new(0, 0, 0, 0),
+                    Metric::from_raw(3),
+                )]
+            ))
+        );
+        assert_eq!(message.to_bytes().unwrap(), bytes);
+
+        let bytes = vec![8, 2, 0, 0, 1, 0, 0, 7];
+        let message = parser::parse(bytes.as_slice()).unwrap();
+        match &message {
+            ParsedMessage::V2(message) => {
+                assert_eq!(
+                    message.get_command(),
+                    command::Kind::TriggeredAcknowledgement
+                );
+                assert_eq!(
+                    message.get_update_header(),
+                    Some(&UpdateHeader::new(false, 7))
+                );
+            }
+            _ => panic!("unexpected version"),
+        }
+        assert_eq!(message.to_bytes().unwrap(), bytes);
+    }
+
+    #[test]
+    fn test_display() {
+        let message = ParsedMessage::V2(RipMessage::UpdateResponse(
+            UpdateHeader::new(true, 7),
+            vec![v2::Entry::new(
+                Identifier::IP,
+                RouteTag::new(100),
+                Ipv4Addr::new(10, 0, 0, 0),
+                Ipv4Addr::new(255, 0, 0, 0),
+                Ipv4Addr::new(192, 0, 2, 1),
+                Metric::from_raw(2),
+            )],
+        ));
+        assert_eq!(
+            message.to_string(),
+            "update-response v2 sequence 7 flush { 10.0.0.0/8 via 192.0.2.1 metric 2 tag 100; }"
+        );
+
+        let message = ParsedMessage::V1(RipMessage::TraceOn(b"/tmp/trace".to_vec()));
+        assert_eq!(message.to_string(), "trace-on v1 file \"/tmp/trace\" { }");
+    }
+
+    #[test]
+    fn test_parse_unsupported_update_header_version() {
+        assert_eq!(
+            parser::parse(vec![11, 2, 0, 0, 2, 0, 0, 7].as_slice()).unwrap_err(),
+            ParseError::UnsupportedUpdateHeaderVersion(2, 4)
+        );
+    }
+
+    #[test]
+    fn test_serialize_message_with_too_many_entries() {
+        let entries = (0..26)
+            .map(|_| {
+                v1::Entry::new(
+                    Identifier::IP,
+                    Ipv4Addr::new(192, 0, 2, 100),
+                    Metric::from_raw(1),
+                )
+            })
+            .collect();
+        assert_eq!(
+            RipMessage::Response(entries).to_bytes().unwrap_err(),
+            SerializeError::MaxRIPEntriesNumberExceeded(26, 25)
+        );
+    }
+
+    #[test]
+    fn test_parse_unsupported_command() {
+        assert_eq!(
+            parser::parse(vec![5, 2, 0, 0].as_slice()).unwrap_err(),
+            ParseError::UnsupportedCommandKind(5, 0)
+        );
+    }
+}
@@ -1,8 +1,84 @@
-use crate::parser::Parsed;
-use crate::serializer::SerializeError;
+use crate::parser::{Parsable, Parsed};
+use crate::serializer::{write_slice, Serializable, SerializeError};
 use crate::{byte_reader, parser::ParseError};
+use std::fmt;
+use thiserror::Error;
 
-pub type Metric = u32;
+#[derive(Error, Debug, PartialEq, Clone)]
+pub enum MetricError {
+    #[error("metric {0} is out of the range 1..=16")]
+    OutOfRange(u32),
+}
+
+/// Metric is the cost of a route.
+/// A valid metric is between 1 and 16 inclusive, and 16 ([`Metric::INFINITY`]) means the destination is unreachable.
+/// The metric keeps the raw value on the wire as-is, so it can represent an invalid value that comes from a packet.
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+pub struct Metric(u32);
+
+impl Metric {
+    pub const INFINITY: Metric = Metric(16);
+
+    /// new makes a metric strictly; it fails when the value is out of the range 1..=16.
+    pub fn new(value: u32) -> Result<Self, MetricError> {
+        let metric = Metric(value);
+        if !metric.is_valid() {
+            return Err(MetricError::OutOfRange(value));
+        }
+        Ok(metric)
+    }
+
+    /// from_raw makes a metric leniently; it keeps the given wire value even if that is out of the range.
+    pub fn from_raw(value: u32) -> Self {
+        Metric(value)
+    }
+
+    /// to_u32 returns the raw value on the wire.
+    pub fn to_u32(&self) -> u32 {
+        self.0
+    }
+
+    pub fn is_valid(&self) -> bool {
+        (1..=16).contains(&self.0)
+    }
+
+    /// is_reachable returns whether the metric is valid and less than the infinity.
+    pub fn is_reachable(&self) -> bool {
+        (1..16).contains(&self.0)
+    }
+
+    /// is_infinity returns whether the metric is the infinity, i.e. the metric is 16 or greater.
+    pub fn is_infinity(&self) -> bool {
+        self.0 >= Metric::INFINITY.0
+    }
+
+    /// saturating_add adds the cost of the interface to the metric; the result saturates at the infinity.
+    pub fn saturating_add(self, cost: u32) -> Self {
+        Metric(self.0.saturating_add(cost).min(Metric::INFINITY.0))
+    }
+}
+
+impl TryFrom<u32> for Metric {
+    type Error = MetricError;
+
+    fn try_from(value: u32) -> Result<Self, Self::Error> {
+        Metric::new(value)
+    }
+}
+
+impl From<Metric> for u32 {
+    fn from(metric: Metric) -> Self {
+        metric.to_u32()
+    }
+}
+
+impl fmt::Display for Metric {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
 
 pub(crate) fn parse(cursor: usize, bytes: &[u8]) -> Result<Parsed<Metric>, ParseError> {
     let (metric_first_byte, cursor) = byte_reader::read(cursor, bytes)?;
@@ -11,19 +87,99 @@ pub(crate) fn parse(cursor: usize, bytes: &[u8]) -> Result<Parsed<Metric>, Parse
     let (metric_fourth_byte, cursor) = byte_reader::read(cursor, bytes)?;
 
     Ok((
-        ((metric_first_byte as Metric) << 24)
-            + ((metric_second_byte as Metric) << 16)
-            + ((metric_third_byte as Metric) << 8)
-            + metric_fourth_byte as Metric,
+        Metric::from_raw(
+            ((metric_first_byte as u32) << 24)
+                + ((metric_second_byte as u32) << 16)
+                + ((metric_third_byte as u32) << 8)
+                + metric_fourth_byte as u32,
+        ),
         cursor,
     ))
 }
 
-pub(crate) fn to_bytes(value: Metric) -> Result<Vec<u8>, SerializeError> {
-    Ok(vec![
-        ((value & 0xff000000) >> 24) as u8,
-        ((value & 0x00ff0000) >> 16) as u8,
-        ((value & 0x0000ff00) >> 8) as u8,
-        (value & 0x000000ff) as u8,
-    ])
+pub(crate) fn write_bytes(metric: Metric, buf: &mut [u8]) -> Result<usize, SerializeError> {
+    let value = metric.to_u32();
+    write_slice(
+        &[
+            ((value & 0xff000000) >> 24) as u8,
+            ((value & 0x00ff0000) >> 16) as u8,
+            ((value & 0x0000ff00) >> 8) as u8,
+            (value & 0x000000ff) as u8,
+        ],
+        buf,
+    )
+}
+
+impl Parsable for Metric {
+    fn parse(cursor: usize, bytes: &[u8]) -> Result<Parsed<Metric>, ParseError> {
+        parse(cursor, bytes)
+    }
+}
+
+impl Serializable for Metric {
+    fn serialized_len(&self) -> usize {
+        4
+    }
+
+    fn write_bytes(&self, buf: &mut [u8]) -> Result<usize, SerializeError> {
+        write_bytes(*self, buf)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::metric::{Metric, MetricError};
+    use crate::parser::Parsable;
+    use crate::serializer::Serializable;
+
+    #[test]
+    fn test_strict_and_lenient_conversion() {
+        assert_eq!(Metric::new(1).unwrap().to_u32(), 1);
+        assert_eq!(Metric::new(16).unwrap(), Metric::INFINITY);
+        assert_eq!(Metric::new(0).unwrap_err(), MetricError::OutOfRange(0));
+        assert_eq!(
+            Metric::try_from(67305985).unwrap_err(),
+            MetricError::OutOfRange(67305985)
+        );
+
+        let metric = Metric::from_raw(67305985);
+        assert_eq!(metric.to_u32(), 67305985);
+        assert_eq!(u32::from(metric), 67305985);
+        assert!(!metric.is_valid());
+    }
+
+    #[test]
+    fn test_reachability() {
+        assert!(Metric::from_raw(1).is_reachable());
+        assert!(Metric::from_raw(15).is_reachable());
+        assert!(!Metric::INFINITY.is_reachable());
+        assert!(!Metric::from_raw(0).is_reachable());
+        assert!(!Metric::from_raw(17).is_reachable());
+
+        assert!(Metric::INFINITY.is_infinity());
+        assert!(Metric::from_raw(17).is_infinity());
+        assert!(!Metric::from_raw(15).is_infinity());
+    }
+
+    #[test]
+    fn test_saturating_add() {
+        assert_eq!(Metric::from_raw(1).saturating_add(1), Metric::from_raw(2));
+        assert_eq!(Metric::from_raw(15).saturating_add(1), Metric::INFINITY);
+        assert_eq!(Metric::from_raw(15).saturating_add(10), Metric::INFINITY);
+        assert_eq!(Metric::INFINITY.saturating_add(1), Metric::INFINITY);
+        assert_eq!(
+            Metric::from_raw(u32::MAX).saturating_add(1),
+            Metric::INFINITY
+        );
+    }
+
+    #[test]
+    fn test_parse_and_write_bytes() {
+        let (metric, cursor) = Metric::parse(0, vec![4, 3, 2, 1].as_slice()).unwrap();
+        assert_eq!(metric, Metric::from_raw(67305985));
+        assert_eq!(cursor, 4);
+
+        assert_eq!(metric.to_bytes().unwrap(), vec![4, 3, 2, 1]);
+        assert_eq!(metric.to_string(), "67305985");
+    }
 }
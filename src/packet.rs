@@ -1,27 +1,45 @@
 use crate::packet::PacketError::VersionInHeaderConflicted;
-use crate::serializer::{Serializable, SerializeError};
-use crate::{header, v1, v2, version};
+use crate::serializer::{check_buffer_length, Serializable, SerializeError};
+use crate::{command, header, v1, v2, version};
+use std::fmt;
 use thiserror::Error;
 
-#[derive(Error, Debug, PartialEq)]
+/// The length of the RIP header in bytes.
+pub const HEADER_LENGTH: usize = 4;
+/// The length of a RIP entry in bytes.
+pub const ENTRY_LENGTH: usize = 20;
+/// The maximum number of the RIP entries in a packet.
+pub const MAX_ENTRIES_NUM: usize = 25;
+/// The maximum length of a serialized RIP packet in bytes.
+pub const MAX_PACKET_LENGTH: usize = HEADER_LENGTH + ENTRY_LENGTH * MAX_ENTRIES_NUM;
+/// The maximum length of a RIP datagram in bytes (RFC2453 section 3.6).
+pub const MAX_DATAGRAM_LENGTH: usize = 512;
+
+#[derive(Error, Debug, PartialEq, Clone)]
 pub enum PacketError {
     #[error("version in the header conflicted")]
     VersionInHeaderConflicted,
     #[error("the number of RIP entries exceeds the maximum number. it allows to have the entries up to 25 in a packet")]
     MaxRIPEntriesNumberExceeded,
+    #[error("the command {0:?} doesn't allow the packet to have no RIP entry")]
+    EmptyRIPEntry(command::Kind),
 }
 
 #[derive(PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct Packet<T> {
     header: header::Header,
     entries: Vec<T>,
 }
 
 impl<T> Packet<T> {
-    fn new(header: header::Header, entries: Vec<T>) -> Result<Self, PacketError> {
-        if entries.len() > 25 {
+    pub(crate) fn new(header: header::Header, entries: Vec<T>) -> Result<Self, PacketError> {
+        if entries.len() > MAX_ENTRIES_NUM {
             return Err(PacketError::MaxRIPEntriesNumberExceeded);
         }
+        if entries.is_empty() && !header.get_command().allows_empty_entries() {
+            return Err(PacketError::EmptyRIPEntry(header.get_command()));
+        }
 
         Ok(Packet { header, entries })
     }
@@ -33,6 +51,30 @@ impl<T> Packet<T> {
     pub fn get_entries(&self) -> &Vec<T> {
         &self.entries
     }
+
+    pub fn into_entries(self) -> Vec<T> {
+        self.entries
+    }
+}
+
+/// The packet is formatted as `response v2 { 10.0.0.0/8 via 0.0.0.0 metric 2 tag 100; }`.
+/// The alternate form (`{:#}`) puts each entry on its own line.
+impl<T: fmt::Display> fmt::Display for Packet<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {{", self.header)?;
+        for entry in &self.entries {
+            if f.alternate() {
+                write!(f, "\n    {};", entry)?;
+            } else {
+                write!(f, " {};", entry)?;
+            }
+        }
+        if f.alternate() && !self.entries.is_empty() {
+            write!(f, "\n}}")
+        } else {
+            write!(f, " }}")
+        }
+    }
 }
 
 impl Packet<v1::Entry> {
@@ -46,6 +88,22 @@ impl Packet<v1::Entry> {
         }
         Packet::new(header, entries)
     }
+
+    /// make_v1_packets splits the entries into as many packets as needed, and each packet has the given header.
+    /// It returns no packet when there is no entry.
+    pub fn make_v1_packets(
+        header: header::Header,
+        entries: Vec<v1::Entry>,
+    ) -> Result<Vec<Self>, PacketError> {
+        let ver = header.get_version();
+        if ver != version::Version::Version1 {
+            return Err(VersionInHeaderConflicted);
+        }
+        chunk_entries(entries, MAX_ENTRIES_NUM)
+            .into_iter()
+            .map(|chunk| Packet::make_v1_packet(header, chunk))
+            .collect()
+    }
 }
 
 impl Packet<v2::Entry> {
@@ -59,17 +117,90 @@ impl Packet<v2::Entry> {
         }
         Packet::new(header, entries)
     }
+
+    /// make_v2_packets splits the entries into as many packets as needed, and each packet has the given header.
+    /// If `authentication` is given, it is prepended to the entries of each packet.
+    /// It returns no packet when there is no entry.
+    pub fn make_v2_packets(
+        header: header::Header,
+        entries: Vec<v2::Entry>,
+        authentication: Option<v2::Entry>,
+    ) -> Result<Vec<Self>, PacketError> {
+        let ver = header.get_version();
+        if ver != version::Version::Version2 {
+            return Err(VersionInHeaderConflicted);
+        }
+        let max_entries_num = MAX_ENTRIES_NUM - usize::from(authentication.is_some());
+        chunk_entries(entries, max_entries_num)
+            .into_iter()
+            .map(|chunk| {
+                let entries = authentication.iter().cloned().chain(chunk).collect();
+                Packet::make_v2_packet(header, entries)
+            })
+            .collect()
+    }
+}
+
+/// chunk_entries splits the entries into the chunks of at most `size` entries in order.
+fn chunk_entries<T>(entries: Vec<T>, size: usize) -> Vec<Vec<T>> {
+    let mut chunks = vec![];
+    let mut entries = entries.into_iter().peekable();
+    while entries.peek().is_some() {
+        chunks.push(entries.by_ref().take(size).collect());
+    }
+    chunks
+}
+
+impl<T: Serializable> Packet<T> {
+    pub fn serialized_len(&self) -> usize {
+        Serializable::serialized_len(self)
+    }
 }
 
 impl<T: Serializable> Serializable for Packet<T> {
-    fn to_bytes(&self) -> Result<Vec<u8>, SerializeError> {
-        let mut entries_bytes = vec![];
+    fn serialized_len(&self) -> usize {
+        self.get_header().serialized_len()
+            + self
+                .get_entries()
+                .iter()
+                .map(|entry| entry.serialized_len())
+                .sum::<usize>()
+    }
+
+    fn write_bytes(&self, buf: &mut [u8]) -> Result<usize, SerializeError> {
+        check_buffer_length(buf, self.serialized_len())?;
 
+        let mut cursor = self.get_header().write_bytes(buf)?;
         for entry in self.get_entries() {
-            entries_bytes.extend(entry.to_bytes()?);
+            cursor += entry.write_bytes(&mut buf[cursor..])?;
         }
 
-        Ok([self.get_header().to_bytes()?, entries_bytes].concat())
+        Ok(cursor)
+    }
+}
+
+/// PacketDef is the deserialized form of a packet that is yet to be validated.
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+#[serde(rename = "Packet")]
+struct PacketDef<T> {
+    header: header::Header,
+    entries: Vec<T>,
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Packet<v1::Entry> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let def = PacketDef::<v1::Entry>::deserialize(deserializer)?;
+        Packet::make_v1_packet(def.header, def.entries).map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Packet<v2::Entry> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let def = PacketDef::<v2::Entry>::deserialize(deserializer)?;
+        Packet::make_v2_packet(def.header, def.entries).map_err(serde::de::Error::custom)
     }
 }
 
@@ -77,11 +208,104 @@ impl<T: Serializable> Serializable for Packet<T> {
 mod tests {
     use crate::address_family::Identifier;
     use crate::header::Header;
+    use crate::metric::Metric;
     use crate::packet::{Packet, PacketError};
+    use crate::route_tag::RouteTag;
     use crate::serializer::Serializable;
-    use crate::{command, v1, version};
+    use crate::{command, v1, v2, version};
     use std::net::Ipv4Addr;
 
+    fn v2_entries(num: u32) -> Vec<v2::Entry> {
+        (0..num)
+            .map(|i| {
+                v2::Entry::new(
+                    Identifier::IP,
+                    RouteTag::default(),
+                    Ipv4Addr::from(0x0a000000 + (i << 8)),
+                    Ipv4Addr::new(255, 255, 255, 0),
+                    Ipv4Addr::UNSPECIFIED,
+                    Metric::from_raw(1),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_make_v2_packets() {
+        let header = Header::new(command::Kind::Response, version::Version::Version2);
+        let entries = v2_entries(60);
+
+        let packets = Packet::make_v2_packets(header, entries, None).unwrap();
+        assert_eq!(
+            packets
+                .iter()
+                .map(|p| p.get_entries().len())
+                .collect::<Vec<_>>(),
+            vec![25, 25, 10]
+        );
+        assert_eq!(packets[1].get_header(), &header);
+        assert_eq!(
+            packets[1].get_entries()[0].get_ip_address(),
+            Ipv4Addr::new(10, 0, 25, 0)
+        );
+        assert_eq!(
+            packets[2].get_entries()[9].get_ip_address(),
+            Ipv4Addr::new(10, 0, 59, 0)
+        );
+
+        let authentication = || {
+            v2::Entry::new(
+                Identifier::AuthenticationPresent,
+                RouteTag::new(2),
+                Ipv4Addr::new(112, 97, 115, 115),
+                Ipv4Addr::new(119, 111, 114, 100),
+                Ipv4Addr::UNSPECIFIED,
+                Metric::from_raw(0),
+            )
+        };
+        let packets =
+            Packet::make_v2_packets(header, v2_entries(60), Some(authentication())).unwrap();
+        assert_eq!(
+            packets
+                .iter()
+                .map(|p| p.get_entries().len())
+                .collect::<Vec<_>>(),
+            vec![25, 25, 13]
+        );
+        assert!(packets
+            .iter()
+            .all(|p| p.get_entries()[0] == authentication()));
+        assert_eq!(
+            packets[1].get_entries()[1].get_ip_address(),
+            Ipv4Addr::new(10, 0, 24, 0)
+        );
+
+        assert!(Packet::make_v2_packets(header, vec![], None)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_make_packets_on_version_conflict() {
+        assert_eq!(
+            Packet::make_v1_packets(
+                Header::new(command::Kind::Response, version::Version::Version2),
+                vec![]
+            )
+            .unwrap_err(),
+            PacketError::VersionInHeaderConflicted
+        );
+        assert_eq!(
+            Packet::make_v2_packets(
+                Header::new(command::Kind::Response, version::Version::Version1),
+                v2_entries(1),
+                None
+            )
+            .unwrap_err(),
+            PacketError::VersionInHeaderConflicted
+        );
+    }
+
     #[test]
     fn test_make_v1_packet_on_version_conflict() {
         assert_eq!(
@@ -94,6 +318,65 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_make_packet_with_empty_entries() {
+        assert!(Packet::make_v2_packet(
+            Header::new(command::Kind::Response, version::Version::Version2),
+            vec![]
+        )
+        .unwrap()
+        .get_entries()
+        .is_empty());
+        assert_eq!(
+            Packet::make_v2_packet(
+                Header::new(command::Kind::Reserved, version::Version::Version2),
+                vec![]
+            )
+            .unwrap_err(),
+            PacketError::EmptyRIPEntry(command::Kind::Reserved)
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde() {
+        let packet = Packet::make_v1_packet(
+            Header::new(command::Kind::Response, version::Version::Version1),
+            vec![v1::Entry::new(
+                Identifier::IP,
+                Ipv4Addr::new(192, 0, 2, 100),
+                Metric::from_raw(1),
+            )],
+        )
+        .unwrap();
+
+        let json = serde_json::to_string(&packet).unwrap();
+        assert_eq!(
+            json,
+            r#"{"header":{"command":"Response","version":"Version1"},"entries":[{"address_family_identifier":"IP","ip_address":"192.0.2.100","metric":1}]}"#
+        );
+        assert_eq!(
+            serde_json::from_str::<Packet<v1::Entry>>(&json).unwrap(),
+            packet
+        );
+
+        let err = serde_json::from_str::<Packet<v2::Entry>>(
+            r#"{"header":{"command":"Response","version":"Version1"},"entries":[]}"#,
+        )
+        .unwrap_err();
+        assert_eq!(err.to_string(), "version in the header conflicted");
+
+        let entry = r#"{"address_family_identifier":"IP","ip_address":"192.0.2.100","metric":1}"#;
+        let err = serde_json::from_str::<Packet<v1::Entry>>(&format!(
+            r#"{{"header":{{"command":"Response","version":"Version1"}},"entries":[{}]}}"#,
+            vec![entry; 26].join(",")
+        ))
+        .unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with("the number of RIP entries exceeds"));
+    }
+
     #[test]
     fn test_make_v2_packet_on_version_conflict() {
         assert_eq!(
@@ -111,32 +394,136 @@ mod tests {
         let result = Packet::new(
             Header::new(command::Kind::Response, version::Version::Version1),
             vec![
-                v1::Entry::new(Identifier::IP, Ipv4Addr::new(192, 0, 2, 101), 1),
-                v1::Entry::new(Identifier::IP, Ipv4Addr::new(192, 0, 2, 102), 2),
-                v1::Entry::new(Identifier::IP, Ipv4Addr::new(192, 0, 2, 103), 3),
-                v1::Entry::new(Identifier::IP, Ipv4Addr::new(192, 0, 2, 104), 4),
-                v1::Entry::new(Identifier::IP, Ipv4Addr::new(192, 0, 2, 105), 5),
-                v1::Entry::new(Identifier::IP, Ipv4Addr::new(192, 0, 2, 106), 6),
-                v1::Entry::new(Identifier::IP, Ipv4Addr::new(192, 0, 2, 107), 7),
-                v1::Entry::new(Identifier::IP, Ipv4Addr::new(192, 0, 2, 108), 8),
-                v1::Entry::new(Identifier::IP, Ipv4Addr::new(192, 0, 2, 109), 9),
-                v1::Entry::new(Identifier::IP, Ipv4Addr::new(192, 0, 2, 110), 10),
-                v1::Entry::new(Identifier::IP, Ipv4Addr::new(192, 0, 2, 111), 11),
-                v1::Entry::new(Identifier::IP, Ipv4Addr::new(192, 0, 2, 112), 12),
-                v1::Entry::new(Identifier::IP, Ipv4Addr::new(192, 0, 2, 113), 13),
-                v1::Entry::new(Identifier::IP, Ipv4Addr::new(192, 0, 2, 114), 14),
-                v1::Entry::new(Identifier::IP, Ipv4Addr::new(192, 0, 2, 115), 15),
-                v1::Entry::new(Identifier::IP, Ipv4Addr::new(192, 0, 2, 116), 16),
-                v1::Entry::new(Identifier::IP, Ipv4Addr::new(192, 0, 2, 117), 17),
-                v1::Entry::new(Identifier::IP, Ipv4Addr::new(192, 0, 2, 118), 18),
-                v1::Entry::new(Identifier::IP, Ipv4Addr::new(192, 0, 2, 119), 19),
-                v1::Entry::new(Identifier::IP, Ipv4Addr::new(192, 0, 2, 120), 20),
-                v1::Entry::new(Identifier::IP, Ipv4Addr::new(192, 0, 2, 121), 21),
-                v1::Entry::new(Identifier::IP, Ipv4Addr::new(192, 0, 2, 122), 22),
-                v1::Entry::new(Identifier::IP, Ipv4Addr::new(192, 0, 2, 123), 23),
-                v1::Entry::new(Identifier::IP, Ipv4Addr::new(192, 0, 2, 124), 24),
-                v1::Entry::new(Identifier::IP, Ipv4Addr::new(192, 0, 2, 125), 25),
-                v1::Entry::new(Identifier::IP, Ipv4Addr::new(192, 0, 2, 126), 26),
+                v1::Entry::new(
+                    Identifier::IP,
+                    Ipv4Addr::new(192, 0, 2, 101),
+                    Metric::from_raw(1),
+                ),
+                v1::Entry::new(
+                    Identifier::IP,
+                    Ipv4Addr::new(192, 0, 2, 102),
+                    Metric::from_raw(2),
+                ),
+                v1::Entry::new(
+                    Identifier::IP,
+                    Ipv4Addr::new(192, 0, 2, 103),
+                    Metric::from_raw(3),
+                ),
+                v1::Entry::new(
+                    Identifier::IP,
+                    Ipv4Addr::new(192, 0, 2, 104),
+                    Metric::from_raw(4),
+                ),
+                v1::Entry::new(
+                    Identifier::IP,
+                    Ipv4Addr::new(192, 0, 2, 105),
+                    Metric::from_raw(5),
+                ),
+                v1::Entry::new(
+                    Identifier::IP,
+                    Ipv4Addr::new(192, 0, 2, 106),
+                    Metric::from_raw(6),
+                ),
+                v1::Entry::new(
+                    Identifier::IP,
+                    Ipv4Addr::new(192, 0, 2, 107),
+                    Metric::from_raw(7),
+                ),
+                v1::Entry::new(
+                    Identifier::IP,
+                    Ipv4Addr::new(192, 0, 2, 108),
+                    Metric::from_raw(8),
+                ),
+                v1::Entry::new(
+                    Identifier::IP,
+                    Ipv4Addr::new(192, 0, 2, 109),
+                    Metric::from_raw(9),
+                ),
+                v1::Entry::new(
+                    Identifier::IP,
+                    Ipv4Addr::new(192, 0, 2, 110),
+                    Metric::from_raw(10),
+                ),
+                v1::Entry::new(
+                    Identifier::IP,
+                    Ipv4Addr::new(192, 0, 2, 111),
+                    Metric::from_raw(11),
+                ),
+                v1::Entry::new(
+                    Identifier::IP,
+                    Ipv4Addr::new(192, 0, 2, 112),
+                    Metric::from_raw(12),
+                ),
+                v1::Entry::new(
+                    Identifier::IP,
+                    Ipv4Addr::new(192, 0, 2, 113),
+                    Metric::from_raw(13),
+                ),
+                v1::Entry::new(
+                    Identifier::IP,
+                    Ipv4Addr::new(192, 0, 2, 114),
+                    Metric::from_raw(14),
+                ),
+                v1::Entry::new(
+                    Identifier::IP,
+                    Ipv4Addr::new(192, 0, 2, 115),
+                    Metric::from_raw(15),
+                ),
+                v1::Entry::new(
+                    Identifier::IP,
+                    Ipv4Addr::new(192, 0, 2, 116),
+                    Metric::from_raw(16),
+                ),
+                v1::Entry::new(
+                    Identifier::IP,
+                    Ipv4Addr::new(192, 0, 2, 117),
+                    Metric::from_raw(17),
+                ),
+                v1::Entry::new(
+                    Identifier::IP,
+                    Ipv4Addr::new(192, 0, 2, 118),
+                    Metric::from_raw(18),
+                ),
+                v1::Entry::new(
+                    Identifier::IP,
+                    Ipv4Addr::new(192, 0, 2, 119),
+                    Metric::from_raw(19),
+                ),
+                v1::Entry::new(
+                    Identifier::IP,
+                    Ipv4Addr::new(192, 0, 2, 120),
+                    Metric::from_raw(20),
+                ),
+                v1::Entry::new(
+                    Identifier::IP,
+                    Ipv4Addr::new(192, 0, 2, 121),
+                    Metric::from_raw(21),
+                ),
+                v1::Entry::new(
+                    Identifier::IP,
+                    Ipv4Addr::new(192, 0, 2, 122),
+                    Metric::from_raw(22),
+                ),
+                v1::Entry::new(
+                    Identifier::IP,
+                    Ipv4Addr::new(192, 0, 2, 123),
+                    Metric::from_raw(23),
+                ),
+                v1::Entry::new(
+                    Identifier::IP,
+                    Ipv4Addr::new(192, 0, 2, 124),
+                    Metric::from_raw(24),
+                ),
+                v1::Entry::new(
+                    Identifier::IP,
+                    Ipv4Addr::new(192, 0, 2, 125),
+                    Metric::from_raw(25),
+                ),
+                v1::Entry::new(
+                    Identifier::IP,
+                    Ipv4Addr::new(192, 0, 2, 126),
+                    Metric::from_raw(26),
+                ),
             ],
         );
         assert_eq!(
@@ -152,7 +539,7 @@ mod tests {
             vec![v1::Entry::new(
                 Identifier::IP,
                 Ipv4Addr::new(192, 0, 2, 101),
-                1,
+                Metric::from_raw(1),
             )],
         )
         .unwrap();
@@ -175,8 +562,16 @@ mod tests {
         let packet = Packet::new(
             Header::new(command::Kind::Response, version::Version::Version1),
             vec![
-                v1::Entry::new(Identifier::IP, Ipv4Addr::new(192, 0, 2, 101), 1),
-                v1::Entry::new(Identifier::IP, Ipv4Addr::new(192, 0, 2, 102), 2),
+                v1::Entry::new(
+                    Identifier::IP,
+                    Ipv4Addr::new(192, 0, 2, 101),
+                    Metric::from_raw(1),
+                ),
+                v1::Entry::new(
+                    Identifier::IP,
+                    Ipv4Addr::new(192, 0, 2, 102),
+                    Metric::from_raw(2),
+                ),
             ],
         )
         .unwrap();
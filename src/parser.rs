@@ -1,14 +1,17 @@
+use crate::entry::{AnyEntry, RouteEntry};
+use crate::message::{parse_body, ParsedMessage};
 use crate::packet::PacketError;
 use crate::parser::ParseError::InvalidPacket;
-use crate::{header, packet, v1, v2, version};
+use crate::serializer::{Serializable, SerializeError};
+use crate::{command, header, packet, v1, v2, version};
+use std::fmt;
+use std::ops::Range;
 use thiserror::Error;
 
-#[derive(Error, Debug, PartialEq)]
+#[derive(Error, Debug, PartialEq, Clone)]
 pub enum ParseError {
     #[error("insufficient input bytes length; at {0} byte")]
     InsufficientInputBytesLength(usize),
-    #[error("unknown command kind {0} has given; at {1} byte")]
-    UnknownCommandKind(u8, usize),
     #[error("unknown version has given; at {0} byte")]
     UnknownVersion(usize),
     #[error("version which indicates \"must be discarded\" has given; at {0} byte")]
@@ -23,42 +26,330 @@ pub enum ParseError {
     MaxRIPEntriesNumberExceeded(usize),
     #[error("invalid packet: {0}")]
     InvalidPacket(PacketError),
+    #[error("command kind {0} is not supported as a message; at {1} byte")]
+    UnsupportedCommandKind(u8, usize),
+    #[error("the command doesn't allow any payload but it has; at {0} byte")]
+    UnexpectedPayload(usize),
+    #[error("the packet length {0} exceeds the maximum length {1}")]
+    PacketTooLong(usize, usize),
+    #[error("{0} trailing byte(s) don't make up a whole RIP entry; at {1} byte")]
+    TrailingBytes(usize, usize),
+    #[error("update header version {0} is not supported; at {1} byte")]
+    UnsupportedUpdateHeaderVersion(u8, usize),
 }
 
-#[derive(Debug)]
+impl ParseError {
+    /// offset returns the byte offset where the error was detected.
+    /// It always points to the head of the offending byte or field.
+    pub fn offset(&self) -> Option<usize> {
+        self.span().map(|span| span.start)
+    }
+
+    /// span returns the byte range of the offending bytes, e.g. the whole address family identifier or
+    /// the first excess entry. It can exceed the input when the input is truncated, and it is None
+    /// for the errors about the packet as a whole.
+    pub fn span(&self) -> Option<Range<usize>> {
+        match self {
+            ParseError::InsufficientInputBytesLength(offset)
+            | ParseError::UnknownVersion(offset)
+            | ParseError::MustBeDiscardedVersion(offset)
+            | ParseError::NotZeroByte(_, offset)
+            | ParseError::UnsupportedCommandKind(_, offset)
+            | ParseError::UnexpectedPayload(offset)
+            | ParseError::UnsupportedUpdateHeaderVersion(_, offset) => Some(*offset..*offset + 1),
+            ParseError::UnknownAddressFamilyIdentifier(_, offset) => Some(*offset..*offset + 2),
+            ParseError::EmptyRIPEntry(offset) => Some(*offset..*offset),
+            ParseError::MaxRIPEntriesNumberExceeded(offset) => {
+                Some(*offset..*offset + packet::ENTRY_LENGTH)
+            }
+            ParseError::TrailingBytes(count, offset) => Some(*offset..*offset + *count),
+            ParseError::PacketTooLong(length, max_length) => Some(*max_length..*length),
+            ParseError::InvalidPacket(_) => None,
+        }
+    }
+}
+
+#[derive(PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ParsedPacket {
     V1(packet::Packet<v1::Entry>),
     V2(packet::Packet<v2::Entry>),
 }
 
+impl ParsedPacket {
+    pub fn get_header(&self) -> &header::Header {
+        match self {
+            ParsedPacket::V1(packet) => packet.get_header(),
+            ParsedPacket::V2(packet) => packet.get_header(),
+        }
+    }
+
+    /// entries returns the entries of the packet regardless of the version.
+    pub fn entries(&self) -> impl Iterator<Item = &dyn RouteEntry> + '_ {
+        let (v1_entries, v2_entries): (&[v1::Entry], &[v2::Entry]) = match self {
+            ParsedPacket::V1(packet) => (packet.get_entries(), &[]),
+            ParsedPacket::V2(packet) => (&[], packet.get_entries()),
+        };
+        v1_entries
+            .iter()
+            .map(|entry| entry as &dyn RouteEntry)
+            .chain(v2_entries.iter().map(|entry| entry as &dyn RouteEntry))
+    }
+
+    pub fn into_entries(self) -> Vec<AnyEntry> {
+        match self {
+            ParsedPacket::V1(packet) => packet
+                .into_entries()
+                .into_iter()
+                .map(AnyEntry::V1)
+                .collect(),
+            ParsedPacket::V2(packet) => packet
+                .into_entries()
+                .into_iter()
+                .map(AnyEntry::V2)
+                .collect(),
+        }
+    }
+}
+
+impl fmt::Display for ParsedPacket {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParsedPacket::V1(packet) => fmt::Display::fmt(packet, f),
+            ParsedPacket::V2(packet) => fmt::Display::fmt(packet, f),
+        }
+    }
+}
+
+impl Serializable for ParsedPacket {
+    fn serialized_len(&self) -> usize {
+        match self {
+            ParsedPacket::V1(packet) => packet.serialized_len(),
+            ParsedPacket::V2(packet) => packet.serialized_len(),
+        }
+    }
+
+    fn write_bytes(&self, buf: &mut [u8]) -> Result<usize, SerializeError> {
+        match self {
+            ParsedPacket::V1(packet) => packet.write_bytes(buf),
+            ParsedPacket::V2(packet) => packet.write_bytes(buf),
+        }
+    }
+}
+
 /// Parsed is a tuple type which has a T-typed value end a cursor for bytes reading.
 pub type Parsed<T> = (T, usize);
 
-pub fn parse(bytes: &[u8]) -> Result<ParsedPacket, ParseError> {
+/// Parsable is a trait for the wire types that can be decoded from the bytes.
+/// `parse` reads the value at the cursor and returns it with the cursor that points to the next byte.
+pub trait Parsable: Sized {
+    fn parse(cursor: usize, bytes: &[u8]) -> Result<Parsed<Self>, ParseError>;
+}
+
+/// parse parses the bytes as a RIP message whose payload is typed by the command.
+/// It rejects the payload that doesn't match the command, e.g. a TraceOff that has entries.
+pub fn parse(bytes: &[u8]) -> Result<ParsedMessage, ParseError> {
+    parse_with_options(bytes, &ParseOptions::default()).map(|(message, _)| message)
+}
+
+/// parse_packet parses the bytes as a RIP packet, i.e. the header followed by the RIP entries, whatever the command is.
+pub fn parse_packet(bytes: &[u8]) -> Result<ParsedPacket, ParseError> {
+    parse_packet_with_options(bytes, &ParseOptions::default()).map(|(packet, _)| packet)
+}
+
+/// TrailingBytesPolicy decides how to treat the bytes that follow the last whole RIP entry,
+/// e.g. the padding that some vendor stacks append to the datagram.
+#[derive(PartialEq, Eq, Copy, Clone, Debug, Default)]
+pub enum TrailingBytesPolicy {
+    /// Reject fails the parsing with [`ParseError::TrailingBytes`].
+    #[default]
+    Reject,
+    /// Ignore drops the trailing bytes.
+    Ignore,
+    /// Return hands over the trailing bytes to the caller.
+    Return,
+}
+
+/// ParseOptions is the options of [`parse_with_options`] and [`parse_packet_with_options`].
+/// By default, it rejects the trailing bytes, the packet that is longer than MAX_DATAGRAM_LENGTH and
+/// the entry that has an unknown address family identifier.
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub struct ParseOptions {
+    trailing_bytes_policy: TrailingBytesPolicy,
+    max_packet_length: usize,
+    allows_unknown_address_family: bool,
+}
+
+impl ParseOptions {
+    pub fn new() -> Self {
+        ParseOptions {
+            trailing_bytes_policy: TrailingBytesPolicy::Reject,
+            max_packet_length: packet::MAX_DATAGRAM_LENGTH,
+            allows_unknown_address_family: false,
+        }
+    }
+
+    pub fn trailing_bytes_policy(mut self, trailing_bytes_policy: TrailingBytesPolicy) -> Self {
+        self.trailing_bytes_policy = trailing_bytes_policy;
+        self
+    }
+
+    pub fn max_packet_length(mut self, max_packet_length: usize) -> Self {
+        self.max_packet_length = max_packet_length;
+        self
+    }
+
+    /// allow_unknown_address_family makes the entries keep the unknown address family identifiers as
+    /// [`crate::address_family::Identifier::Unknown`], so that such a packet can be re-serialized as is.
+    pub fn allow_unknown_address_family(mut self, allows_unknown_address_family: bool) -> Self {
+        self.allows_unknown_address_family = allows_unknown_address_family;
+        self
+    }
+
+    pub fn get_trailing_bytes_policy(&self) -> TrailingBytesPolicy {
+        self.trailing_bytes_policy
+    }
+
+    pub fn get_max_packet_length(&self) -> usize {
+        self.max_packet_length
+    }
+
+    pub fn allows_unknown_address_family(&self) -> bool {
+        self.allows_unknown_address_family
+    }
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        ParseOptions::new()
+    }
+}
+
+/// parse_with_options parses the bytes as a RIP message with the options.
+/// It returns the message with the trailing bytes; they are empty unless the policy is [`TrailingBytesPolicy::Return`].
+pub fn parse_with_options<'a>(
+    bytes: &'a [u8],
+    options: &ParseOptions,
+) -> Result<(ParsedMessage, &'a [u8]), ParseError> {
     let (header, cursor) = header::parse(0, bytes)?;
+    check_max_length(bytes, options)?;
+    let command = header.get_command();
+    let (v1_parser, v2_parser) = entries_parsers(options);
 
-    match header.get_version() {
-        version::Version::Version1 => match parse_entries(&v1::EntriesParser {}, cursor, bytes) {
-            Ok(entries) => Ok(ParsedPacket::V1(
-                packet::Packet::make_v1_packet(header, entries).unwrap(),
-            )),
-            Err(e) => Err(e),
-        },
-        version::Version::Version2 => match parse_entries(&v2::EntriesParser {}, cursor, bytes) {
-            Ok(entries) => Ok(ParsedPacket::V2(
-                packet::Packet::make_v2_packet(header, entries).unwrap(),
-            )),
-            Err(e) => Err(e),
-        },
-        version::Version::MustBeDiscarded => Err(ParseError::MustBeDiscardedVersion(2)),
-        version::Version::Unknown => Err(ParseError::UnknownVersion(2)),
+    let (message, end) = match header.get_version() {
+        version::Version::Version1 => {
+            let (message, end) = parse_body(command, v1_parser, cursor, bytes, options)?;
+            (ParsedMessage::V1(message), end)
+        }
+        version::Version::Version2 => {
+            let (message, end) = parse_body(command, v2_parser, cursor, bytes, options)?;
+            (ParsedMessage::V2(message), end)
+        }
+        version::Version::MustBeDiscarded => return Err(ParseError::MustBeDiscardedVersion(1)),
+        version::Version::Unknown(_) => return Err(ParseError::UnknownVersion(1)),
+    };
+
+    Ok((message, trailing_bytes(bytes, end, options)))
+}
+
+/// parse_packet_with_options parses the bytes as a RIP packet with the options.
+/// It returns the packet with the trailing bytes; they are empty unless the policy is [`TrailingBytesPolicy::Return`].
+pub fn parse_packet_with_options<'a>(
+    bytes: &'a [u8],
+    options: &ParseOptions,
+) -> Result<(ParsedPacket, &'a [u8]), ParseError> {
+    let (header, cursor) = header::parse(0, bytes)?;
+    let end = check_length(bytes, options)?;
+    let trailing_bytes = trailing_bytes(bytes, end, options);
+    let bytes = &bytes[..end];
+    let (v1_parser, v2_parser) = entries_parsers(options);
+
+    let packet = match header.get_version() {
+        version::Version::Version1 => ParsedPacket::V1(
+            packet::Packet::make_v1_packet(
+                header,
+                parse_command_entries(header.get_command(), v1_parser, cursor, bytes)?,
+            )
+            .map_err(InvalidPacket)?,
+        ),
+        version::Version::Version2 => ParsedPacket::V2(
+            packet::Packet::make_v2_packet(
+                header,
+                parse_command_entries(header.get_command(), v2_parser, cursor, bytes)?,
+            )
+            .map_err(InvalidPacket)?,
+        ),
+        version::Version::MustBeDiscarded => return Err(ParseError::MustBeDiscardedVersion(1)),
+        version::Version::Unknown(_) => return Err(ParseError::UnknownVersion(1)),
+    };
+
+    Ok((packet, trailing_bytes))
+}
+
+/// entries_parsers returns the entry parsers of both versions that follow the options.
+fn entries_parsers(
+    options: &ParseOptions,
+) -> (
+    &'static dyn PacketParsable<v1::Entry>,
+    &'static dyn PacketParsable<v2::Entry>,
+) {
+    if options.allows_unknown_address_family() {
+        (
+            &v1::AnyAddressFamilyEntriesParser {},
+            &v2::AnyAddressFamilyEntriesParser {},
+        )
+    } else {
+        (&v1::EntriesParser {}, &v2::EntriesParser {})
+    }
+}
+
+/// trailing_bytes returns the bytes that follow `end` if the policy hands them over to the caller.
+fn trailing_bytes<'a>(bytes: &'a [u8], end: usize, options: &ParseOptions) -> &'a [u8] {
+    match options.get_trailing_bytes_policy() {
+        TrailingBytesPolicy::Return => &bytes[end..],
+        _ => &bytes[end..end],
+    }
+}
+
+/// check_length validates the length of the packet, which must consist of the header and whole RIP entries.
+/// It returns the end of the last whole entry.
+pub(crate) fn check_length(bytes: &[u8], options: &ParseOptions) -> Result<usize, ParseError> {
+    check_entries_length(packet::HEADER_LENGTH, bytes, options)
+}
+
+/// check_entries_length validates the length of the packet whose RIP entries start at `entries_start`,
+/// e.g. after the update header of RFC2091. It returns the end of the last whole entry.
+pub(crate) fn check_entries_length(
+    entries_start: usize,
+    bytes: &[u8],
+    options: &ParseOptions,
+) -> Result<usize, ParseError> {
+    check_max_length(bytes, options)?;
+
+    let trailing = bytes.len().saturating_sub(entries_start) % packet::ENTRY_LENGTH;
+    let end = bytes.len() - trailing;
+    if trailing != 0 && options.get_trailing_bytes_policy() == TrailingBytesPolicy::Reject {
+        return Err(ParseError::TrailingBytes(trailing, end));
+    }
+
+    Ok(end)
+}
+
+fn check_max_length(bytes: &[u8], options: &ParseOptions) -> Result<(), ParseError> {
+    if bytes.len() > options.get_max_packet_length() {
+        return Err(ParseError::PacketTooLong(
+            bytes.len(),
+            options.get_max_packet_length(),
+        ));
     }
+    Ok(())
 }
 
 pub fn parse_v1(bytes: &[u8]) -> Result<packet::Packet<v1::Entry>, ParseError> {
     let (header, cursor) = header::parse(0, bytes)?;
+    check_length(bytes, &ParseOptions::default())?;
 
-    match parse_entries(&v1::EntriesParser {}, cursor, bytes) {
+    match parse_command_entries(header.get_command(), &v1::EntriesParser {}, cursor, bytes) {
         Ok(entries) => match packet::Packet::make_v1_packet(header, entries) {
             Ok(p) => Ok(p),
             Err(e) => Err(InvalidPacket(e)),
@@ -69,8 +360,9 @@ pub fn parse_v1(bytes: &[u8]) -> Result<packet::Packet<v1::Entry>, ParseError> {
 
 pub fn parse_v2(bytes: &[u8]) -> Result<packet::Packet<v2::Entry>, ParseError> {
     let (header, cursor) = header::parse(0, bytes)?;
+    check_length(bytes, &ParseOptions::default())?;
 
-    match parse_entries(&v2::EntriesParser {}, cursor, bytes) {
+    match parse_command_entries(header.get_command(), &v2::EntriesParser {}, cursor, bytes) {
         Ok(entries) => match packet::Packet::make_v2_packet(header, entries) {
             Ok(p) => Ok(p),
             Err(e) => Err(InvalidPacket(e)),
@@ -79,19 +371,79 @@ pub fn parse_v2(bytes: &[u8]) -> Result<packet::Packet<v2::Entry>, ParseError> {
     }
 }
 
-pub(crate) fn parse_entries<T>(
+/// parse_with_entry_parser parses the bytes as a RIP packet whose entries are decoded by the given entry parser.
+/// This validates the header, the packet length and the number of the entries as well as [`parse_packet`], so it can be
+/// used for the vendor-specific entry formats. The trailing bytes are not checked since the length of such an entry
+/// may differ from ENTRY_LENGTH; a truncated entry is reported by the entry parser instead.
+pub fn parse_with_entry_parser<T>(
     parser: &dyn PacketParsable<T>,
-    mut cursor: usize,
+    bytes: &[u8],
+) -> Result<packet::Packet<T>, ParseError> {
+    let (header, cursor) = header::parse(0, bytes)?;
+    check_max_length(bytes, &ParseOptions::default())?;
+
+    match header.get_version() {
+        version::Version::Version1 | version::Version::Version2 => {}
+        version::Version::MustBeDiscarded => return Err(ParseError::MustBeDiscardedVersion(1)),
+        version::Version::Unknown(_) => return Err(ParseError::UnknownVersion(1)),
+    }
+
+    let entries = parse_command_entries(header.get_command(), parser, cursor, bytes)?;
+    match packet::Packet::new(header, entries) {
+        Ok(p) => Ok(p),
+        Err(e) => Err(InvalidPacket(e)),
+    }
+}
+
+pub fn parse_entries<T>(
+    parser: &dyn PacketParsable<T>,
+    cursor: usize,
+    bytes: &[u8],
+) -> Result<Vec<T>, ParseError> {
+    let mut entries: Vec<T> = vec![];
+    walk_entries(parser, cursor, bytes, false, |entry| entries.push(entry))?;
+    Ok(entries)
+}
+
+/// parse_command_entries parses the RIP entries part as well as [`parse_entries`],
+/// but it accepts the empty entries part if the command allows that.
+pub(crate) fn parse_command_entries<T>(
+    command: command::Kind,
+    parser: &dyn PacketParsable<T>,
+    cursor: usize,
     bytes: &[u8],
 ) -> Result<Vec<T>, ParseError> {
     let mut entries: Vec<T> = vec![];
+    walk_entries(
+        parser,
+        cursor,
+        bytes,
+        command.allows_empty_entries(),
+        |entry| entries.push(entry),
+    )?;
+    Ok(entries)
+}
+
+/// walk_entries parses the RIP entries part and hands over each entry to the callback in order.
+/// It returns the number of the parsed entries. The empty entries part is an error unless `allow_empty` is true.
+pub(crate) fn walk_entries<T>(
+    parser: &dyn PacketParsable<T>,
+    mut cursor: usize,
+    bytes: &[u8],
+    allow_empty: bool,
+    mut on_entry: impl FnMut(T),
+) -> Result<usize, ParseError> {
+    let mut num_of_entries = 0;
 
-    if (bytes.len() - 1) <= cursor {
+    if cursor >= bytes.len() {
+        if allow_empty {
+            return Ok(0);
+        }
         return Err(ParseError::EmptyRIPEntry(cursor));
     }
 
     loop {
-        if entries.len() >= 25 {
+        if num_of_entries >= packet::MAX_ENTRIES_NUM {
             return Err(ParseError::MaxRIPEntriesNumberExceeded(cursor));
         }
 
@@ -101,7 +453,8 @@ pub(crate) fn parse_entries<T>(
                 return Err(e);
             }
         };
-        entries.push(res.0);
+        on_entry(res.0);
+        num_of_entries += 1;
         cursor = res.1;
 
         if cursor >= bytes.len() {
@@ -109,24 +462,50 @@ pub(crate) fn parse_entries<T>(
         }
     }
 
-    Ok(entries)
+    Ok(num_of_entries)
 }
 
-pub(crate) trait PacketParsable<T> {
+/// PacketParsable is a trait to parse a RIP entry in the packet.
+/// `parse_entry` reads an entry at the cursor and returns it with the cursor that points to the next byte.
+pub trait PacketParsable<T> {
     fn parse_entry<'a>(&'a self, cursor: usize, bytes: &'a [u8]) -> Result<(T, usize), ParseError>;
 }
 
 #[cfg(test)]
 mod tests {
+    use crate::metric::Metric;
+    use crate::packet::PacketError;
     use crate::packet::PacketError::VersionInHeaderConflicted;
-    use crate::parser::ParseError;
-    use crate::parser::ParseError::{InsufficientInputBytesLength, InvalidPacket};
+    use crate::parser::ParseError::{InvalidPacket, TrailingBytes};
+    use crate::parser::{PacketParsable, Parsable, ParseError, ParseOptions, TrailingBytesPolicy};
+    use crate::route_tag::RouteTag;
+    use crate::serializer::Serializable;
     use crate::{address_family, command, header::Header, packet::Packet, parser, v1, v2, version};
     use std::net::Ipv4Addr;
 
+    #[derive(PartialEq, Debug)]
+    struct CostEntry {
+        entry: v2::Entry,
+        cost: Metric,
+    }
+
+    struct CostEntriesParser {}
+
+    impl PacketParsable<CostEntry> for CostEntriesParser {
+        fn parse_entry<'a>(
+            &'a self,
+            cursor: usize,
+            bytes: &'a [u8],
+        ) -> Result<(CostEntry, usize), ParseError> {
+            let (entry, cursor) = v2::Entry::parse(cursor, bytes)?;
+            let (cost, cursor) = Metric::parse(cursor, bytes)?;
+            Ok((CostEntry { entry, cost }, cursor))
+        }
+    }
+
     #[test]
     fn test_parse_v1_packet_for_single_entry() {
-        let result = parser::parse(
+        let result = parser::parse_packet(
             vec![
                 2, 1, 0, 0, //
                 0, 2, 0, 0, //
@@ -138,7 +517,7 @@ mod tests {
             .as_slice(),
         );
 
-        assert_eq!(result.is_ok(), true);
+        assert!(result.is_ok());
 
         let packet = match result.unwrap() {
             parser::ParsedPacket::V1(p) => p,
@@ -155,7 +534,7 @@ mod tests {
             vec![v1::Entry::new(
                 address_family::Identifier::IP,
                 Ipv4Addr::new(192, 0, 2, 100),
-                67305985,
+                Metric::from_raw(67305985),
             )],
         )
         .unwrap();
@@ -164,7 +543,7 @@ mod tests {
 
     #[test]
     fn test_parse_v1_packet_for_multiple_entry() {
-        let result = parser::parse(
+        let result = parser::parse_packet(
             vec![
                 2, 1, 0, 0, //
                 0, 2, 0, 0, //
@@ -185,7 +564,7 @@ mod tests {
             ]
             .as_slice(),
         );
-        assert_eq!(result.is_ok(), true);
+        assert!(result.is_ok());
 
         let packet = match result.unwrap() {
             parser::ParsedPacket::V1(p) => p,
@@ -203,17 +582,17 @@ mod tests {
                 v1::Entry::new(
                     address_family::Identifier::IP,
                     Ipv4Addr::new(192, 0, 2, 100),
-                    67305985,
+                    Metric::from_raw(67305985),
                 ),
                 v1::Entry::new(
                     address_family::Identifier::IP,
                     Ipv4Addr::new(192, 0, 2, 101),
-                    1,
+                    Metric::from_raw(1),
                 ),
                 v1::Entry::new(
                     address_family::Identifier::IP,
                     Ipv4Addr::new(192, 0, 2, 102),
-                    2,
+                    Metric::from_raw(2),
                 ),
             ],
         )
@@ -223,7 +602,7 @@ mod tests {
 
     #[test]
     fn test_parse_v2_packet_for_single_entry() {
-        let result = parser::parse(
+        let result = parser::parse_packet(
             vec![
                 2, 2, 0, 0, //
                 0, 2, 1, 2, //
@@ -235,7 +614,7 @@ mod tests {
             .as_slice(),
         );
 
-        assert_eq!(result.is_ok(), true);
+        assert!(result.is_ok());
 
         let packet = match result.unwrap() {
             parser::ParsedPacket::V1(_) => {
@@ -251,11 +630,11 @@ mod tests {
             Header::new(command::Kind::Response, version::Version::Version2),
             vec![v2::Entry::new(
                 address_family::Identifier::IP,
-                258,
+                RouteTag::new(258),
                 Ipv4Addr::new(192, 0, 2, 100),
                 Ipv4Addr::new(255, 255, 255, 0),
                 Ipv4Addr::new(192, 0, 2, 111),
-                67305985,
+                Metric::from_raw(67305985),
             )],
         )
         .unwrap();
@@ -264,7 +643,7 @@ mod tests {
 
     #[test]
     fn test_parse_v2_packet_for_multiple_entry() {
-        let result = parser::parse(
+        let result = parser::parse_packet(
             vec![
                 2, 2, 0, 0, //
                 0, 2, 1, 2, //
@@ -285,7 +664,7 @@ mod tests {
             ]
             .as_slice(),
         );
-        assert_eq!(result.is_ok(), true);
+        assert!(result.is_ok());
 
         let packet = match result.unwrap() {
             parser::ParsedPacket::V1(_) => {
@@ -302,27 +681,27 @@ mod tests {
             vec![
                 v2::Entry::new(
                     address_family::Identifier::IP,
-                    258,
+                    RouteTag::new(258),
                     Ipv4Addr::new(192, 0, 2, 100),
                     Ipv4Addr::new(255, 255, 255, 0),
                     Ipv4Addr::new(192, 0, 2, 200),
-                    67305985,
+                    Metric::from_raw(67305985),
                 ),
                 v2::Entry::new(
                     address_family::Identifier::IP,
-                    1,
+                    RouteTag::new(1),
                     Ipv4Addr::new(192, 0, 2, 101),
                     Ipv4Addr::new(255, 255, 255, 0),
                     Ipv4Addr::new(192, 0, 2, 201),
-                    1,
+                    Metric::from_raw(1),
                 ),
                 v2::Entry::new(
                     address_family::Identifier::IP,
-                    2,
+                    RouteTag::new(2),
                     Ipv4Addr::new(192, 0, 2, 102),
                     Ipv4Addr::new(255, 255, 255, 0),
                     Ipv4Addr::new(192, 0, 2, 202),
-                    2,
+                    Metric::from_raw(2),
                 ),
             ],
         )
@@ -332,7 +711,7 @@ mod tests {
 
     #[test]
     fn test_parse_insufficient_length_bytes_for_v1() {
-        let result = parser::parse(
+        let result = parser::parse_packet(
             vec![
                 2, 1, 0, 0, //
                 0, 2, 0, 0, //
@@ -344,15 +723,12 @@ mod tests {
             .as_slice(),
         );
 
-        assert_eq!(
-            result.unwrap_err(),
-            ParseError::InsufficientInputBytesLength(23)
-        );
+        assert_eq!(result.unwrap_err(), ParseError::TrailingBytes(19, 4));
     }
 
     #[test]
     fn test_parse_insufficient_length_bytes_for_v2() {
-        let result = parser::parse(
+        let result = parser::parse_packet(
             vec![
                 2, 2, 0, 0, //
                 0, 2, 0, 0, //
@@ -364,15 +740,12 @@ mod tests {
             .as_slice(),
         );
 
-        assert_eq!(
-            result.unwrap_err(),
-            ParseError::InsufficientInputBytesLength(23)
-        );
+        assert_eq!(result.unwrap_err(), ParseError::TrailingBytes(19, 4));
     }
 
     #[test]
     fn test_parse_bytes_must_be_discarded() {
-        let result = parser::parse(
+        let result = parser::parse_packet(
             vec![
                 2, 0, 0, 0, // version byte is 0 (must be discarded)
                 0, 2, 0, 0, //
@@ -384,12 +757,12 @@ mod tests {
             .as_slice(),
         );
 
-        assert_eq!(result.unwrap_err(), ParseError::MustBeDiscardedVersion(2),);
+        assert_eq!(result.unwrap_err(), ParseError::MustBeDiscardedVersion(1),);
     }
 
     #[test]
     fn test_parse_bytes_of_unknown_version() {
-        let result = parser::parse(
+        let result = parser::parse_packet(
             vec![
                 2, 255, 0, 0, // version byte is 255 (unknown)
                 0, 2, 0, 0, //
@@ -401,19 +774,30 @@ mod tests {
             .as_slice(),
         );
 
-        assert_eq!(result.unwrap_err(), ParseError::UnknownVersion(2));
+        assert_eq!(result.unwrap_err(), ParseError::UnknownVersion(1));
     }
 
     #[test]
     fn test_parse_empty_entry_part() {
-        let result = parser::parse(vec![2, 2, 0, 0].as_slice());
-
+        let result = parser::parse_packet(vec![5, 2, 0, 0].as_slice());
         assert_eq!(result.unwrap_err(), ParseError::EmptyRIPEntry(4));
+
+        match parser::parse_packet(vec![2, 2, 0, 0].as_slice()).unwrap() {
+            parser::ParsedPacket::V2(packet) => {
+                assert_eq!(packet.get_header().get_command(), command::Kind::Response);
+                assert!(packet.get_entries().is_empty());
+            }
+            _ => panic!("unexpected version"),
+        }
+        assert!(parser::parse_v1(vec![1, 1, 0, 0].as_slice())
+            .unwrap()
+            .get_entries()
+            .is_empty());
     }
 
     #[test]
     fn test_parse_bytes_which_has_the_number_of_entries_that_exceeds_max_limit() {
-        let result = parser::parse(
+        let result = parser::parse_packet(
             vec![
                 2, 2, 0, 0, //
                 0, 2, 1, 2, 192, 0, 2, 101, 255, 255, 255, 0, 192, 0, 2, 200, 0, 0, 0, 1, //
@@ -446,12 +830,183 @@ mod tests {
             .as_slice(),
         );
 
+        assert_eq!(result.unwrap_err(), ParseError::PacketTooLong(524, 512));
+    }
+
+    #[test]
+    fn test_parse_entries_which_exceeds_max_limit() {
+        let mut bytes = vec![2, 2, 0, 0];
+        for _ in 0..26 {
+            bytes.extend([
+                0, 2, 0, 0, 192, 0, 2, 0, 255, 255, 255, 0, 0, 0, 0, 0, 0, 0, 0, 1,
+            ]);
+        }
+
         assert_eq!(
-            result.unwrap_err(),
+            parser::parse_entries(&v2::EntriesParser {}, 4, bytes.as_slice()).unwrap_err(),
             ParseError::MaxRIPEntriesNumberExceeded(504)
         );
     }
 
+    #[test]
+    fn test_parse_with_unknown_address_family() {
+        let bytes = vec![
+            2, 2, 0, 0, //
+            0, 1, 0, 0, // unknown address family identifier
+            192, 0, 2, 0, //
+            255, 255, 255, 0, //
+            0, 0, 0, 0, //
+            0, 0, 0, 1, //
+        ];
+        assert_eq!(
+            parser::parse_packet_with_options(bytes.as_slice(), &ParseOptions::default())
+                .unwrap_err(),
+            ParseError::UnknownAddressFamilyIdentifier(1, 4)
+        );
+
+        let options = ParseOptions::default().allow_unknown_address_family(true);
+        let (packet, _) = parser::parse_packet_with_options(bytes.as_slice(), &options).unwrap();
+        assert_eq!(
+            packet
+                .entries()
+                .map(|entry| entry.address_family_identifier())
+                .collect::<Vec<_>>(),
+            vec![address_family::Identifier::Unknown(1)]
+        );
+        assert_eq!(packet.to_bytes().unwrap(), bytes);
+
+        let mut bytes = bytes;
+        bytes[1] = 1;
+        bytes[6..20].fill(0);
+        let (packet, _) = parser::parse_packet_with_options(bytes.as_slice(), &options).unwrap();
+        assert_eq!(packet.to_bytes().unwrap(), bytes);
+    }
+
+    #[test]
+    fn test_parse_with_trailing_bytes_policy() {
+        let bytes = vec![
+            2, 2, 0, 0, //
+            0, 2, 0, 0, //
+            192, 0, 2, 0, //
+            255, 255, 255, 0, //
+            0, 0, 0, 0, //
+            0, 0, 0, 1, //
+            0, 0, 0, // padding
+        ];
+
+        assert_eq!(
+            parser::parse_packet_with_options(bytes.as_slice(), &ParseOptions::default())
+                .unwrap_err(),
+            ParseError::TrailingBytes(3, 24)
+        );
+
+        let (packet, trailing_bytes) = parser::parse_packet_with_options(
+            bytes.as_slice(),
+            &ParseOptions::new().trailing_bytes_policy(TrailingBytesPolicy::Ignore),
+        )
+        .unwrap();
+        assert_eq!(packet.entries().count(), 1);
+        assert!(trailing_bytes.is_empty());
+
+        let (packet, trailing_bytes) = parser::parse_packet_with_options(
+            bytes.as_slice(),
+            &ParseOptions::new().trailing_bytes_policy(TrailingBytesPolicy::Return),
+        )
+        .unwrap();
+        assert_eq!(packet.entries().count(), 1);
+        assert_eq!(trailing_bytes, &[0, 0, 0]);
+
+        assert_eq!(
+            parser::parse_packet_with_options(
+                bytes.as_slice(),
+                &ParseOptions::new()
+                    .trailing_bytes_policy(TrailingBytesPolicy::Ignore)
+                    .max_packet_length(24)
+            )
+            .unwrap_err(),
+            ParseError::PacketTooLong(27, 24)
+        );
+    }
+
+    #[test]
+    fn test_parse_message_with_options() {
+        let bytes = vec![
+            10, 2, 0, 0, //
+            1, 0, 0, 7, // update header
+            0, 1, 0, 0, // unknown address family identifier
+            192, 0, 2, 0, //
+            255, 255, 255, 0, //
+            0, 0, 0, 0, //
+            0, 0, 0, 1, //
+            0, 0, 0, // padding
+        ];
+
+        assert_eq!(
+            parser::parse(bytes.as_slice()).unwrap_err(),
+            ParseError::TrailingBytes(3, 28)
+        );
+        assert_eq!(
+            parser::parse(&bytes[..28]).unwrap_err(),
+            ParseError::UnknownAddressFamilyIdentifier(1, 8)
+        );
+
+        let options = ParseOptions::new()
+            .trailing_bytes_policy(TrailingBytesPolicy::Return)
+            .allow_unknown_address_family(true);
+        let (message, trailing_bytes) =
+            parser::parse_with_options(bytes.as_slice(), &options).unwrap();
+        assert_eq!(message.get_command(), command::Kind::UpdateResponse);
+        assert_eq!(
+            message
+                .entries()
+                .map(|entry| entry.address_family_identifier())
+                .collect::<Vec<_>>(),
+            vec![address_family::Identifier::Unknown(1)]
+        );
+        assert_eq!(trailing_bytes, &[0, 0, 0]);
+        assert_eq!(message.to_bytes().unwrap(), bytes[..28]);
+    }
+
+    #[test]
+    fn test_parse_unsupported_command_as_packet() {
+        let bytes = vec![
+            5, 2, 0, 0, //
+            0, 2, 0, 0, //
+            192, 0, 2, 0, //
+            255, 255, 255, 0, //
+            0, 0, 0, 0, //
+            0, 0, 0, 1, //
+        ];
+        assert_eq!(
+            parser::parse(bytes.as_slice()).unwrap_err(),
+            ParseError::UnsupportedCommandKind(5, 0)
+        );
+        assert_eq!(
+            parser::parse_packet(bytes.as_slice())
+                .unwrap()
+                .get_header()
+                .get_command(),
+            command::Kind::Reserved
+        );
+    }
+
+    #[test]
+    fn test_error_span() {
+        assert_eq!(ParseError::NotZeroByte(1, 14).span(), Some(14..15));
+        assert_eq!(
+            ParseError::UnknownAddressFamilyIdentifier(1, 24).span(),
+            Some(24..26)
+        );
+        assert_eq!(ParseError::EmptyRIPEntry(4).span(), Some(4..4));
+        assert_eq!(ParseError::TrailingBytes(3, 24).span(), Some(24..27));
+        assert_eq!(ParseError::PacketTooLong(600, 512).span(), Some(512..600));
+        assert_eq!(ParseError::PacketTooLong(600, 512).offset(), Some(512));
+        assert_eq!(
+            ParseError::InvalidPacket(PacketError::MaxRIPEntriesNumberExceeded).span(),
+            None
+        );
+    }
+
     #[test]
     fn test_parse_v1() {
         let result = parser::parse_v1(
@@ -471,7 +1026,7 @@ mod tests {
             vec![v1::Entry::new(
                 address_family::Identifier::IP,
                 Ipv4Addr::new(192, 0, 2, 100),
-                67305985,
+                Metric::from_raw(67305985),
             )],
         )
         .unwrap();
@@ -510,7 +1065,7 @@ mod tests {
             ]
             .as_slice(),
         );
-        assert_eq!(result.unwrap_err(), InsufficientInputBytesLength(23));
+        assert_eq!(result.unwrap_err(), TrailingBytes(19, 4));
     }
 
     #[test]
@@ -531,11 +1086,11 @@ mod tests {
             Header::new(command::Kind::Response, version::Version::Version2),
             vec![v2::Entry::new(
                 address_family::Identifier::IP,
-                0,
+                RouteTag::new(0),
                 Ipv4Addr::new(192, 0, 2, 100),
                 Ipv4Addr::new(255, 255, 255, 0),
                 Ipv4Addr::new(0, 0, 0, 0),
-                67305985,
+                Metric::from_raw(67305985),
             )],
         )
         .unwrap();
@@ -574,6 +1129,108 @@ mod tests {
             ]
             .as_slice(),
         );
-        assert_eq!(result.unwrap_err(), InsufficientInputBytesLength(23));
+        assert_eq!(result.unwrap_err(), TrailingBytes(19, 4));
+    }
+
+    #[test]
+    fn test_parse_with_entry_parser() {
+        let packet = parser::parse_with_entry_parser(
+            &CostEntriesParser {},
+            vec![
+                2, 2, 0, 0, //
+                0, 2, 1, 2, //
+                192, 0, 2, 100, //
+                255, 255, 255, 0, //
+                192, 0, 2, 111, //
+                0, 0, 0, 1, //
+                0, 0, 1, 0, // extra cost field
+                0, 2, 0, 0, //
+                192, 0, 2, 101, //
+                255, 255, 255, 0, //
+                0, 0, 0, 0, //
+                0, 0, 0, 2, //
+                0, 0, 0, 10, // extra cost field
+            ]
+            .as_slice(),
+        )
+        .unwrap();
+
+        assert_eq!(
+            packet.get_header(),
+            &Header::new(command::Kind::Response, version::Version::Version2)
+        );
+        assert_eq!(
+            packet.get_entries(),
+            &vec![
+                CostEntry {
+                    entry: v2::Entry::new(
+                        address_family::Identifier::IP,
+                        RouteTag::new(258),
+                        Ipv4Addr::new(192, 0, 2, 100),
+                        Ipv4Addr::new(255, 255, 255, 0),
+                        Ipv4Addr::new(192, 0, 2, 111),
+                        Metric::from_raw(1),
+                    ),
+                    cost: Metric::from_raw(256),
+                },
+                CostEntry {
+                    entry: v2::Entry::new(
+                        address_family::Identifier::IP,
+                        RouteTag::new(0),
+                        Ipv4Addr::new(192, 0, 2, 101),
+                        Ipv4Addr::new(255, 255, 255, 0),
+                        Ipv4Addr::new(0, 0, 0, 0),
+                        Metric::from_raw(2),
+                    ),
+                    cost: Metric::from_raw(10),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_with_entry_parser_validates_header_and_entries() {
+        assert_eq!(
+            parser::parse_with_entry_parser(&CostEntriesParser {}, vec![2, 0, 0, 0].as_slice())
+                .unwrap_err(),
+            ParseError::MustBeDiscardedVersion(1)
+        );
+        assert_eq!(
+            parser::parse_with_entry_parser(&CostEntriesParser {}, vec![0, 2, 0, 0].as_slice())
+                .unwrap_err(),
+            ParseError::EmptyRIPEntry(4)
+        );
+        assert_eq!(
+            parser::parse_with_entry_parser(
+                &CostEntriesParser {},
+                vec![
+                    2, 2, 0, 0, //
+                    0, 2, 1, 2, //
+                    192, 0, 2, 100, //
+                    255, 255, 255, 0, //
+                    192, 0, 2, 111, //
+                    0, 0, 0, 1, // missing the extra cost field
+                ]
+                .as_slice()
+            )
+            .unwrap_err(),
+            ParseError::InsufficientInputBytesLength(24)
+        );
+
+        let mut bytes = vec![2, 2, 0, 0];
+        for _ in 0..22 {
+            bytes.extend([
+                0, 2, 0, 0, //
+                192, 0, 2, 100, //
+                255, 255, 255, 0, //
+                0, 0, 0, 0, //
+                0, 0, 0, 1, //
+                0, 0, 0, 10, //
+            ]);
+        }
+        assert_eq!(
+            parser::parse_with_entry_parser(&CostEntriesParser {}, bytes.as_slice()).unwrap_err(),
+            ParseError::PacketTooLong(532, 512)
+        );
     }
 }
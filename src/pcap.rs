@@ -0,0 +1,1213 @@
+use crate::encapsulation::{encapsulate, EncapsulationError, IP_PROTOCOL_UDP};
+use crate::message::ParsedMessage;
+use crate::packet::Packet;
+use crate::parser::{self, ParseError};
+use crate::serializer::{serialize_v1_packet, serialize_v2_packet, SerializeError};
+use crate::{v1, v2};
+use std::io;
+use std::io::{Read, Write};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use thiserror::Error;
+
+/// The UDP port of RIPv1 and RIPv2.
+pub const RIP_PORT: u16 = 520;
+/// The UDP port of RIPng, which this crate doesn't support.
+pub const RIPNG_PORT: u16 = 521;
+
+/// The link types (https://www.tcpdump.org/linktypes.html) that the reader can decode.
+pub const LINKTYPE_ETHERNET: u32 = 1;
+pub const LINKTYPE_RAW: u32 = 101;
+pub const LINKTYPE_LINUX_SLL: u32 = 113;
+pub const LINKTYPE_IPV4: u32 = 228;
+pub const LINKTYPE_IPV6: u32 = 229;
+pub const LINKTYPE_LINUX_SLL2: u32 = 276;
+
+const PCAP_MAGIC_MICROSECONDS: u32 = 0xa1b2c3d4;
+const PCAP_MAGIC_NANOSECONDS: u32 = 0xa1b23c4d;
+const PCAPNG_SECTION_HEADER_BLOCK: u32 = 0x0a0d0d0a;
+const PCAPNG_BYTE_ORDER_MAGIC: u32 = 0x1a2b3c4d;
+const PCAPNG_INTERFACE_DESCRIPTION_BLOCK: u32 = 1;
+const PCAPNG_PACKET_BLOCK: u32 = 2;
+const PCAPNG_SIMPLE_PACKET_BLOCK: u32 = 3;
+const PCAPNG_ENHANCED_PACKET_BLOCK: u32 = 6;
+const PCAPNG_OPTION_IF_TSRESOL: u16 = 9;
+
+/// The maximum length of a record that the reader accepts; a longer record means that the file is corrupt.
+const MAX_RECORD_LENGTH: usize = 16 * 1024 * 1024;
+
+/// The snapshot length of the files that [`Writer`] writes.
+const SNAPSHOT_LENGTH: u32 = 65535;
+
+/// The multicast address of RIPv2 routers (RFC2453 section 4.5).
+pub const RIP2_ROUTERS: Ipv4Addr = Ipv4Addr::new(224, 0, 0, 9);
+
+const ETHERTYPE_IPV4: u16 = 0x0800;
+const ETHERTYPE_IPV6: u16 = 0x86dd;
+const ETHERTYPE_VLAN: u16 = 0x8100;
+const ETHERTYPE_QINQ: u16 = 0x88a8;
+const ETHERTYPE_QINQ_LEGACY: u16 = 0x9100;
+
+#[derive(Error, Debug)]
+pub enum PcapError {
+    #[error("I/O error: {0}")]
+    Io(#[from] io::Error),
+    #[error("unknown capture file format; the magic number is {0:#010x}")]
+    UnknownFormat(u32),
+    #[error("corrupt capture file at {0} byte")]
+    CorruptFile(u64),
+    #[error("link type {0} is not supported; at frame {1}")]
+    UnsupportedLinkType(u32, u64),
+    #[error("malformed frame {1}: {0}")]
+    MalformedFrame(&'static str, u64),
+    #[error("invalid RIP packet in frame {1}: {0}")]
+    InvalidRipPacket(ParseError, u64),
+    #[error("failed to serialize the packet: {0}")]
+    Serialize(#[from] SerializeError),
+    #[error("failed to encapsulate the packet: {0}")]
+    Encapsulation(#[from] EncapsulationError),
+}
+
+/// Capture is a RIP message that is extracted from a frame of a capture file.
+#[derive(Debug)]
+pub struct Capture {
+    frame_number: u64,
+    timestamp: SystemTime,
+    source: SocketAddr,
+    destination: SocketAddr,
+    payload: Vec<u8>,
+    message: ParsedMessage,
+}
+
+impl Capture {
+    /// get_frame_number returns the 1-origin number of the frame in the file, as Wireshark numbers it.
+    pub fn get_frame_number(&self) -> u64 {
+        self.frame_number
+    }
+
+    pub fn get_timestamp(&self) -> SystemTime {
+        self.timestamp
+    }
+
+    pub fn get_source(&self) -> SocketAddr {
+        self.source
+    }
+
+    pub fn get_destination(&self) -> SocketAddr {
+        self.destination
+    }
+
+    /// get_payload returns the UDP payload as captured, which the message is parsed from.
+    pub fn get_payload(&self) -> &[u8] {
+        &self.payload
+    }
+
+    pub fn get_message(&self) -> &ParsedMessage {
+        &self.message
+    }
+
+    /// into_parts returns the timestamp, the source, the destination, the message and the payload.
+    pub fn into_parts(self) -> (SystemTime, SocketAddr, SocketAddr, ParsedMessage, Vec<u8>) {
+        (
+            self.timestamp,
+            self.source,
+            self.destination,
+            self.message,
+            self.payload,
+        )
+    }
+}
+
+/// Endian is the byte order of the fields in a capture file.
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+enum Endian {
+    Little,
+    Big,
+}
+
+impl Endian {
+    fn u16(self, bytes: &[u8]) -> u16 {
+        let bytes = [bytes[0], bytes[1]];
+        match self {
+            Endian::Little => u16::from_le_bytes(bytes),
+            Endian::Big => u16::from_be_bytes(bytes),
+        }
+    }
+
+    fn u32(self, bytes: &[u8]) -> u32 {
+        let bytes = [bytes[0], bytes[1], bytes[2], bytes[3]];
+        match self {
+            Endian::Little => u32::from_le_bytes(bytes),
+            Endian::Big => u32::from_be_bytes(bytes),
+        }
+    }
+}
+
+/// TimestampResolution is the unit of the timestamps; `Decimal(n)` is 10^-n seconds and `Binary(n)` is 2^-n seconds.
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+enum TimestampResolution {
+    Decimal(u8),
+    Binary(u8),
+}
+
+impl TimestampResolution {
+    /// to_system_time converts the timestamp; it returns None when the timestamp is out of the range of SystemTime.
+    fn to_system_time(self, units: u64) -> Option<SystemTime> {
+        let units_per_second: u128 = match self {
+            TimestampResolution::Decimal(n) => 10u128.saturating_pow(n as u32),
+            TimestampResolution::Binary(n) => 1u128.checked_shl(n as u32).unwrap_or(u128::MAX),
+        };
+        let units = units as u128;
+        let seconds = (units / units_per_second) as u64;
+        let nanos = (units % units_per_second * 1_000_000_000 / units_per_second) as u32;
+        UNIX_EPOCH.checked_add(Duration::new(seconds, nanos))
+    }
+}
+
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+struct Interface {
+    link_type: u32,
+    resolution: TimestampResolution,
+}
+
+#[derive(PartialEq, Eq, Clone, Debug)]
+enum Format {
+    Pcap {
+        endian: Endian,
+        interface: Interface,
+    },
+    Pcapng {
+        endian: Endian,
+        interfaces: Vec<Interface>,
+    },
+}
+
+/// Frame is a captured frame that is read from a record of a capture file.
+struct Frame {
+    link_type: Option<u32>,
+    timestamp: Option<SystemTime>,
+    data: Vec<u8>,
+}
+
+/// Reader reads the RIP packets from a pcap or pcapng file.
+/// It decodes Ethernet (with VLAN tags), Linux cooked (SLL and SLL2) and raw IP frames, IPv4/IPv6 and UDP,
+/// and extracts the datagrams from or to the RIP port. The datagrams of RIPng are skipped
+/// since this crate doesn't implement RIPng.
+/// An error about a frame is reported with its frame number and the reader continues from the next frame,
+/// while an error about the file ends the reading.
+pub struct Reader<R: Read> {
+    reader: R,
+    format: Format,
+    offset: u64,
+    frame_number: u64,
+    done: bool,
+}
+
+impl<R: Read> Reader<R> {
+    /// new reads the file header and detects whether the file is pcap or pcapng.
+    pub fn new(mut reader: R) -> Result<Self, PcapError> {
+        let mut magic = [0; 4];
+        reader.read_exact(&mut magic)?;
+
+        let format = match u32::from_le_bytes(magic) {
+            PCAPNG_SECTION_HEADER_BLOCK => Format::Pcapng {
+                endian: Endian::Little,
+                interfaces: vec![],
+            },
+            _ => {
+                let (endian, resolution) =
+                    match (u32::from_le_bytes(magic), u32::from_be_bytes(magic)) {
+                        (PCAP_MAGIC_MICROSECONDS, _) => {
+                            (Endian::Little, TimestampResolution::Decimal(6))
+                        }
+                        (PCAP_MAGIC_NANOSECONDS, _) => {
+                            (Endian::Little, TimestampResolution::Decimal(9))
+                        }
+                        (_, PCAP_MAGIC_MICROSECONDS) => {
+                            (Endian::Big, TimestampResolution::Decimal(6))
+                        }
+                        (_, PCAP_MAGIC_NANOSECONDS) => {
+                            (Endian::Big, TimestampResolution::Decimal(9))
+                        }
+                        (_, magic) => return Err(PcapError::UnknownFormat(magic)),
+                    };
+
+                let mut header = [0; 20];
+                reader.read_exact(&mut header)?;
+                Format::Pcap {
+                    endian,
+                    interface: Interface {
+                        link_type: endian.u32(&header[16..20]) & 0x0fff_ffff,
+                        resolution,
+                    },
+                }
+            }
+        };
+
+        let mut pcap_reader = Reader {
+            reader,
+            format,
+            offset: 4,
+            frame_number: 0,
+            done: false,
+        };
+        if let Format::Pcap { .. } = pcap_reader.format {
+            pcap_reader.offset = 24;
+        } else {
+            pcap_reader.read_section_header(magic)?;
+        }
+        Ok(pcap_reader)
+    }
+
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+
+    /// read_exact reads the bytes of a record; it returns false at the end of the file before the record.
+    fn read_exact(&mut self, buf: &mut [u8], at_boundary: bool) -> Result<bool, PcapError> {
+        let mut read = 0;
+        while read < buf.len() {
+            match self.reader.read(&mut buf[read..]) {
+                Ok(0) if at_boundary && read == 0 => return Ok(false),
+                Ok(0) => return Err(PcapError::CorruptFile(self.offset + read as u64)),
+                Ok(n) => read += n,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(PcapError::Io(e)),
+            }
+        }
+        self.offset += read as u64;
+        Ok(true)
+    }
+
+    fn read_body(&mut self, len: usize) -> Result<Vec<u8>, PcapError> {
+        if len > MAX_RECORD_LENGTH {
+            return Err(PcapError::CorruptFile(self.offset));
+        }
+        let mut body = vec![0; len];
+        self.read_exact(&mut body, false)?;
+        Ok(body)
+    }
+
+    /// read_section_header reads the rest of a section header block of pcapng whose type has been read.
+    fn read_section_header(&mut self, block_type: [u8; 4]) -> Result<(), PcapError> {
+        let head = self.offset - block_type.len() as u64;
+        let mut fields = [0; 8];
+        self.read_exact(&mut fields, false)?;
+
+        let endian = match u32::from_le_bytes([fields[4], fields[5], fields[6], fields[7]]) {
+            PCAPNG_BYTE_ORDER_MAGIC => Endian::Little,
+            _ if u32::from_be_bytes([fields[4], fields[5], fields[6], fields[7]])
+                == PCAPNG_BYTE_ORDER_MAGIC =>
+            {
+                Endian::Big
+            }
+            _ => return Err(PcapError::CorruptFile(head + 8)),
+        };
+        let total_length = endian.u32(&fields[0..4]) as usize;
+        if total_length < 28 || !total_length.is_multiple_of(4) {
+            return Err(PcapError::CorruptFile(head + 4));
+        }
+        self.read_body(total_length - 12)?;
+
+        self.format = Format::Pcapng {
+            endian,
+            interfaces: vec![],
+        };
+        Ok(())
+    }
+
+    /// read_frame reads records up to the next frame; it returns None at the end of the file.
+    fn read_frame(&mut self) -> Result<Option<Frame>, PcapError> {
+        loop {
+            if let Some(frame) = self.read_record()? {
+                return Ok(frame);
+            }
+        }
+    }
+
+    /// read_record reads a record; it returns None when the record is not a frame, e.g. an interface description.
+    fn read_record(&mut self) -> Result<Option<Option<Frame>>, PcapError> {
+        match self.format.clone() {
+            Format::Pcap { endian, interface } => {
+                let mut header = [0; 16];
+                if !self.read_exact(&mut header, true)? {
+                    return Ok(Some(None));
+                }
+                let seconds = endian.u32(&header[0..4]) as u64;
+                let fraction = endian.u32(&header[4..8]) as u64;
+                let units_per_second = match interface.resolution {
+                    TimestampResolution::Decimal(6) => 1_000_000,
+                    _ => 1_000_000_000,
+                };
+                let data = self.read_body(endian.u32(&header[8..12]) as usize)?;
+
+                Ok(Some(Some(Frame {
+                    link_type: Some(interface.link_type),
+                    timestamp: interface
+                        .resolution
+                        .to_system_time(seconds * units_per_second + fraction),
+                    data,
+                })))
+            }
+            Format::Pcapng { endian, interfaces } => {
+                let head = self.offset;
+                let mut block_type = [0; 4];
+                if !self.read_exact(&mut block_type, true)? {
+                    return Ok(Some(None));
+                }
+                if u32::from_le_bytes(block_type) == PCAPNG_SECTION_HEADER_BLOCK {
+                    self.read_section_header(block_type)?;
+                    return Ok(None);
+                }
+
+                let mut total_length = [0; 4];
+                self.read_exact(&mut total_length, false)?;
+                let total_length = endian.u32(&total_length) as usize;
+                if total_length < 12 || !total_length.is_multiple_of(4) {
+                    return Err(PcapError::CorruptFile(head + 4));
+                }
+                let block = self.read_body(total_length - 8)?;
+                let body = &block[..block.len() - 4];
+
+                match endian.u32(&block_type) {
+                    PCAPNG_INTERFACE_DESCRIPTION_BLOCK => {
+                        let interface = parse_interface_description(endian, body)
+                            .ok_or(PcapError::CorruptFile(head + 8))?;
+                        if let Format::Pcapng { interfaces, .. } = &mut self.format {
+                            interfaces.push(interface);
+                        }
+                        Ok(None)
+                    }
+                    PCAPNG_ENHANCED_PACKET_BLOCK | PCAPNG_PACKET_BLOCK => {
+                        if body.len() < 20 {
+                            return Err(PcapError::CorruptFile(head + 8));
+                        }
+                        let interface_id = match endian.u32(&block_type) {
+                            PCAPNG_PACKET_BLOCK => endian.u16(&body[0..2]) as usize,
+                            _ => endian.u32(&body[0..4]) as usize,
+                        };
+                        let units = ((endian.u32(&body[4..8]) as u64) << 32)
+                            | endian.u32(&body[8..12]) as u64;
+                        let captured_length = endian.u32(&body[12..16]) as usize;
+                        let data = body
+                            .get(20..20 + captured_length)
+                            .ok_or(PcapError::CorruptFile(head + 20))?;
+                        let interface = interfaces.get(interface_id);
+
+                        Ok(Some(Some(Frame {
+                            link_type: interface.map(|i| i.link_type),
+                            timestamp: interface
+                                .map(|i| i.resolution)
+                                .unwrap_or(TimestampResolution::Decimal(6))
+                                .to_system_time(units),
+                            data: data.to_vec(),
+                        })))
+                    }
+                    PCAPNG_SIMPLE_PACKET_BLOCK => {
+                        if body.len() < 4 {
+                            return Err(PcapError::CorruptFile(head + 8));
+                        }
+                        let original_length = endian.u32(&body[0..4]) as usize;
+                        let data = &body[4..];
+
+                        // the simple packet block doesn't have the timestamp.
+                        Ok(Some(Some(Frame {
+                            link_type: interfaces.first().map(|i| i.link_type),
+                            timestamp: Some(UNIX_EPOCH),
+                            data: data[..original_length.min(data.len())].to_vec(),
+                        })))
+                    }
+                    _ => Ok(None),
+                }
+            }
+        }
+    }
+
+    /// decode extracts the RIP packet from the frame; it returns None when the frame is not a RIP datagram.
+    fn decode(&self, frame: Frame) -> Result<Option<Capture>, PcapError> {
+        let frame_number = self.frame_number;
+        let link_type = frame.link_type.ok_or(PcapError::MalformedFrame(
+            "no interface for the frame",
+            frame_number,
+        ))?;
+
+        let timestamp = frame.timestamp.ok_or(PcapError::MalformedFrame(
+            "timestamp out of range",
+            frame_number,
+        ))?;
+
+        let datagram = match decode_frame(link_type, &frame.data) {
+            Ok(Some(datagram)) => datagram,
+            Ok(None) => return Ok(None),
+            Err(FrameError::UnsupportedLinkType) => {
+                return Err(PcapError::UnsupportedLinkType(link_type, frame_number))
+            }
+            Err(FrameError::Malformed(reason)) => {
+                return Err(PcapError::MalformedFrame(reason, frame_number))
+            }
+        };
+
+        let ports = [datagram.source.port(), datagram.destination.port()];
+        if !ports.contains(&RIP_PORT) {
+            return Ok(None);
+        }
+
+        let message = parser::parse(datagram.payload)
+            .map_err(|e| PcapError::InvalidRipPacket(e, frame_number))?;
+        Ok(Some(Capture {
+            frame_number,
+            timestamp,
+            source: datagram.source,
+            destination: datagram.destination,
+            payload: datagram.payload.to_vec(),
+            message,
+        }))
+    }
+}
+
+impl<R: Read> Iterator for Reader<R> {
+    type Item = Result<Capture, PcapError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.done {
+            let frame = match self.read_frame() {
+                Ok(Some(frame)) => frame,
+                Ok(None) => {
+                    self.done = true;
+                    return None;
+                }
+                Err(e) => {
+                    self.done = true;
+                    return Some(Err(e));
+                }
+            };
+            self.frame_number += 1;
+
+            match self.decode(frame) {
+                Ok(Some(capture)) => return Some(Ok(capture)),
+                Ok(None) => {}
+                Err(e) => return Some(Err(e)),
+            }
+        }
+        None
+    }
+}
+
+/// parse_interface_description parses the body of an interface description block of pcapng.
+fn parse_interface_description(endian: Endian, body: &[u8]) -> Option<Interface> {
+    let link_type = endian.u16(body.get(0..2)?) as u32;
+    let mut resolution = TimestampResolution::Decimal(6);
+
+    let mut cursor = 8;
+    while cursor + 4 <= body.len() {
+        let code = endian.u16(&body[cursor..cursor + 2]);
+        let len = endian.u16(&body[cursor + 2..cursor + 4]) as usize;
+        if code == 0 {
+            break;
+        }
+        let value = body.get(cursor + 4..cursor + 4 + len)?;
+        if code == PCAPNG_OPTION_IF_TSRESOL && len == 1 {
+            resolution = match value[0] & 0x80 {
+                0 => TimestampResolution::Decimal(value[0]),
+                _ => TimestampResolution::Binary(value[0] & 0x7f),
+            };
+        }
+        cursor += 4 + len.div_ceil(4) * 4;
+    }
+
+    Some(Interface {
+        link_type,
+        resolution,
+    })
+}
+
+/// Datagram is a UDP datagram from or to the RIP or RIPng port.
+#[derive(PartialEq, Debug)]
+struct Datagram<'a> {
+    source: SocketAddr,
+    destination: SocketAddr,
+    payload: &'a [u8],
+}
+
+#[derive(PartialEq, Debug)]
+enum FrameError {
+    UnsupportedLinkType,
+    Malformed(&'static str),
+}
+
+/// decode_frame extracts the UDP datagram of RIP or RIPng from the frame of the link type.
+fn decode_frame(link_type: u32, data: &[u8]) -> Result<Option<Datagram<'_>>, FrameError> {
+    match link_type {
+        LINKTYPE_ETHERNET => {
+            let ethertype = data
+                .get(12..14)
+                .ok_or(FrameError::Malformed("truncated Ethernet header"))?;
+            decode_ethertype(
+                u16::from_be_bytes([ethertype[0], ethertype[1]]),
+                &data[14..],
+            )
+        }
+        LINKTYPE_LINUX_SLL => {
+            let protocol = data
+                .get(14..16)
+                .ok_or(FrameError::Malformed("truncated Linux cooked header"))?;
+            decode_ethertype(u16::from_be_bytes([protocol[0], protocol[1]]), &data[16..])
+        }
+        LINKTYPE_LINUX_SLL2 => {
+            if data.len() < 20 {
+                return Err(FrameError::Malformed("truncated Linux cooked header"));
+            }
+            decode_ethertype(u16::from_be_bytes([data[0], data[1]]), &data[20..])
+        }
+        LINKTYPE_RAW | LINKTYPE_IPV4 | LINKTYPE_IPV6 => match data.first().map(|b| b >> 4) {
+            Some(4) => decode_ipv4(data),
+            Some(6) => decode_ipv6(data),
+            _ => Err(FrameError::Malformed("unknown IP version")),
+        },
+        _ => Err(FrameError::UnsupportedLinkType),
+    }
+}
+
+/// decode_ethertype decodes the payload of the ethertype, skipping the VLAN tags.
+fn decode_ethertype(
+    mut ethertype: u16,
+    mut data: &[u8],
+) -> Result<Option<Datagram<'_>>, FrameError> {
+    while matches!(
+        ethertype,
+        ETHERTYPE_VLAN | ETHERTYPE_QINQ | ETHERTYPE_QINQ_LEGACY
+    ) {
+        let tag = data
+            .get(0..4)
+            .ok_or(FrameError::Malformed("truncated VLAN tag"))?;
+        ethertype = u16::from_be_bytes([tag[2], tag[3]]);
+        data = &data[4..];
+    }
+
+    match ethertype {
+        ETHERTYPE_IPV4 => decode_ipv4(data),
+        ETHERTYPE_IPV6 => decode_ipv6(data),
+        _ => Ok(None),
+    }
+}
+
+fn decode_ipv4(data: &[u8]) -> Result<Option<Datagram<'_>>, FrameError> {
+    if data.len() < 20 || data[0] >> 4 != 4 {
+        return Err(FrameError::Malformed("truncated IPv4 header"));
+    }
+    let header_length = (data[0] & 0x0f) as usize * 4;
+    let total_length = u16::from_be_bytes([data[2], data[3]]) as usize;
+    if header_length < 20 || total_length < header_length {
+        return Err(FrameError::Malformed("invalid IPv4 header length"));
+    }
+    if total_length > data.len() {
+        return Err(FrameError::Malformed("truncated IPv4 packet"));
+    }
+    if data[9] != IP_PROTOCOL_UDP {
+        return Ok(None);
+    }
+
+    let fragment = u16::from_be_bytes([data[6], data[7]]);
+    let more_fragments = fragment & 0x2000 != 0;
+    if fragment & 0x1fff != 0 {
+        // the following fragments don't have the UDP header.
+        return Ok(None);
+    }
+
+    let source = Ipv4Addr::new(data[12], data[13], data[14], data[15]);
+    let destination = Ipv4Addr::new(data[16], data[17], data[18], data[19]);
+    let datagram = decode_udp(
+        IpAddr::V4(source),
+        IpAddr::V4(destination),
+        &data[header_length..total_length],
+        more_fragments,
+    )?;
+    Ok(datagram)
+}
+
+fn decode_ipv6(data: &[u8]) -> Result<Option<Datagram<'_>>, FrameError> {
+    if data.len() < 40 || data[0] >> 4 != 6 {
+        return Err(FrameError::Malformed("truncated IPv6 header"));
+    }
+    let payload_length = u16::from_be_bytes([data[4], data[5]]) as usize;
+    if 40 + payload_length > data.len() {
+        return Err(FrameError::Malformed("truncated IPv6 packet"));
+    }
+    let source = <[u8; 16]>::try_from(&data[8..24]).map(Ipv6Addr::from);
+    let destination = <[u8; 16]>::try_from(&data[24..40]).map(Ipv6Addr::from);
+    let (source, destination) = match (source, destination) {
+        (Ok(source), Ok(destination)) => (source, destination),
+        _ => return Err(FrameError::Malformed("truncated IPv6 header")),
+    };
+
+    let mut next_header = data[6];
+    let mut payload = &data[40..40 + payload_length];
+    loop {
+        match next_header {
+            IP_PROTOCOL_UDP => {
+                return decode_udp(IpAddr::V6(source), IpAddr::V6(destination), payload, false)
+            }
+            // hop-by-hop options, routing and destination options
+            0 | 43 | 60 => {
+                let extension = payload
+                    .get(0..2)
+                    .ok_or(FrameError::Malformed("truncated IPv6 extension header"))?;
+                let len = (extension[1] as usize + 1) * 8;
+                next_header = extension[0];
+                payload = payload
+                    .get(len..)
+                    .ok_or(FrameError::Malformed("truncated IPv6 extension header"))?;
+            }
+            // fragment
+            44 => {
+                let extension = payload
+                    .get(0..8)
+                    .ok_or(FrameError::Malformed("truncated IPv6 extension header"))?;
+                let fragment = u16::from_be_bytes([extension[2], extension[3]]);
+                if fragment & 0xfff8 != 0 {
+                    return Ok(None);
+                }
+                if extension[0] != IP_PROTOCOL_UDP {
+                    return Ok(None);
+                }
+                return decode_udp(
+                    IpAddr::V6(source),
+                    IpAddr::V6(destination),
+                    &payload[8..],
+                    fragment & 0x0001 != 0,
+                );
+            }
+            _ => return Ok(None),
+        }
+    }
+}
+
+/// decode_udp decodes the UDP datagram and returns it when it is from or to the RIP or RIPng port.
+fn decode_udp(
+    source: IpAddr,
+    destination: IpAddr,
+    data: &[u8],
+    fragmented: bool,
+) -> Result<Option<Datagram<'_>>, FrameError> {
+    if data.len() < 8 {
+        return Err(FrameError::Malformed("truncated UDP header"));
+    }
+    let source_port = u16::from_be_bytes([data[0], data[1]]);
+    let destination_port = u16::from_be_bytes([data[2], data[3]]);
+    let ports = [source_port, destination_port];
+    if !ports.contains(&RIP_PORT) && !ports.contains(&RIPNG_PORT) {
+        return Ok(None);
+    }
+    if fragmented {
+        return Err(FrameError::Malformed("fragmented UDP datagram"));
+    }
+
+    let length = u16::from_be_bytes([data[4], data[5]]) as usize;
+    if length < 8 || length > data.len() {
+        return Err(FrameError::Malformed("invalid UDP length"));
+    }
+
+    Ok(Some(Datagram {
+        source: SocketAddr::new(source, source_port),
+        destination: SocketAddr::new(destination, destination_port),
+        payload: &data[8..length],
+    }))
+}
+
+/// FrameOptions is the addresses and the timestamp of the frames that [`Writer`] writes.
+/// By default, the frame is sent from 192.0.2.1:520 (MAC address 02:00:00:00:00:01) to 224.0.0.9:520 at the UNIX epoch.
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub struct FrameOptions {
+    source: SocketAddrV4,
+    destination: SocketAddrV4,
+    source_mac: [u8; 6],
+    destination_mac: Option<[u8; 6]>,
+    timestamp: SystemTime,
+}
+
+impl FrameOptions {
+    pub fn new() -> Self {
+        FrameOptions {
+            source: SocketAddrV4::new(Ipv4Addr::new(192, 0, 2, 1), RIP_PORT),
+            destination: SocketAddrV4::new(RIP2_ROUTERS, RIP_PORT),
+            source_mac: [0x02, 0, 0, 0, 0, 0x01],
+            destination_mac: None,
+            timestamp: UNIX_EPOCH,
+        }
+    }
+
+    pub fn source(mut self, source: SocketAddrV4) -> Self {
+        self.source = source;
+        self
+    }
+
+    pub fn destination(mut self, destination: SocketAddrV4) -> Self {
+        self.destination = destination;
+        self
+    }
+
+    pub fn source_mac(mut self, source_mac: [u8; 6]) -> Self {
+        self.source_mac = source_mac;
+        self
+    }
+
+    /// destination_mac overrides the destination MAC address that is derived from the destination IP address.
+    pub fn destination_mac(mut self, destination_mac: [u8; 6]) -> Self {
+        self.destination_mac = Some(destination_mac);
+        self
+    }
+
+    pub fn timestamp(mut self, timestamp: SystemTime) -> Self {
+        self.timestamp = timestamp;
+        self
+    }
+
+    pub fn get_source(&self) -> SocketAddrV4 {
+        self.source
+    }
+
+    pub fn get_destination(&self) -> SocketAddrV4 {
+        self.destination
+    }
+
+    pub fn get_source_mac(&self) -> [u8; 6] {
+        self.source_mac
+    }
+
+    /// get_destination_mac returns the destination MAC address. Unless it is overridden, it is the multicast MAC
+    /// address of the destination (e.g. 01:00:5e:00:00:09 for 224.0.0.9) or the broadcast MAC address otherwise.
+    pub fn get_destination_mac(&self) -> [u8; 6] {
+        if let Some(destination_mac) = self.destination_mac {
+            return destination_mac;
+        }
+
+        let destination = self.destination.ip().octets();
+        if self.destination.ip().is_multicast() {
+            [
+                0x01,
+                0x00,
+                0x5e,
+                destination[1] & 0x7f,
+                destination[2],
+                destination[3],
+            ]
+        } else {
+            [0xff; 6]
+        }
+    }
+
+    pub fn get_timestamp(&self) -> SystemTime {
+        self.timestamp
+    }
+}
+
+impl Default for FrameOptions {
+    fn default() -> Self {
+        FrameOptions::new()
+    }
+}
+
+/// Writer writes the RIP packets into a pcap file as Ethernet/IPv4/UDP frames that have the correct checksums
+/// and TTL 1, as a router sends them.
+pub struct Writer<W: Write> {
+    writer: W,
+}
+
+impl<W: Write> Writer<W> {
+    /// new writes the file header of pcap whose link type is Ethernet.
+    pub fn new(mut writer: W) -> Result<Self, PcapError> {
+        let mut header = vec![];
+        header.extend(PCAP_MAGIC_MICROSECONDS.to_le_bytes());
+        header.extend(2u16.to_le_bytes());
+        header.extend(4u16.to_le_bytes());
+        header.extend([0; 8]);
+        header.extend(SNAPSHOT_LENGTH.to_le_bytes());
+        header.extend(LINKTYPE_ETHERNET.to_le_bytes());
+        writer.write_all(&header)?;
+
+        Ok(Writer { writer })
+    }
+
+    /// write_v1_packet writes the packet as a frame and returns the length of the frame.
+    pub fn write_v1_packet(
+        &mut self,
+        packet: Packet<v1::Entry>,
+        options: &FrameOptions,
+    ) -> Result<usize, PcapError> {
+        self.write_payload(&serialize_v1_packet(packet)?, options)
+    }
+
+    /// write_v2_packet writes the packet as a frame and returns the length of the frame.
+    pub fn write_v2_packet(
+        &mut self,
+        packet: Packet<v2::Entry>,
+        options: &FrameOptions,
+    ) -> Result<usize, PcapError> {
+        self.write_payload(&serialize_v2_packet(packet)?, options)
+    }
+
+    /// write_payload writes the bytes as the UDP payload of a frame, e.g. a deliberately malformed packet.
+    pub fn write_payload(
+        &mut self,
+        payload: &[u8],
+        options: &FrameOptions,
+    ) -> Result<usize, PcapError> {
+        let frame = ethernet_frame(
+            options,
+            &encapsulate(options.get_source(), options.get_destination(), 1, payload)?,
+        );
+
+        let since_epoch = options
+            .get_timestamp()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        let mut header = vec![];
+        header.extend((since_epoch.as_secs() as u32).to_le_bytes());
+        header.extend(since_epoch.subsec_micros().to_le_bytes());
+        header.extend((frame.len() as u32).to_le_bytes());
+        header.extend((frame.len() as u32).to_le_bytes());
+        self.writer.write_all(&header)?;
+        self.writer.write_all(&frame)?;
+
+        Ok(frame.len())
+    }
+
+    pub fn flush(&mut self) -> Result<(), PcapError> {
+        Ok(self.writer.flush()?)
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+fn ethernet_frame(options: &FrameOptions, payload: &[u8]) -> Vec<u8> {
+    let mut frame = Vec::with_capacity(14 + payload.len());
+    frame.extend(options.get_destination_mac());
+    frame.extend(options.get_source_mac());
+    frame.extend(ETHERTYPE_IPV4.to_be_bytes());
+    frame.extend(payload);
+    frame
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::encapsulation::checksum;
+    use crate::header::Header;
+    use crate::message::{ParsedMessage, RipMessage};
+    use crate::metric::Metric;
+    use crate::packet::Packet;
+    use crate::parser::ParseError;
+    use crate::pcap::{
+        decode_frame, FrameError, FrameOptions, PcapError, Reader, Writer, LINKTYPE_ETHERNET,
+        LINKTYPE_LINUX_SLL,
+    };
+    use crate::route_tag::RouteTag;
+    use crate::{address_family, command, v2, version};
+    use std::net::{Ipv4Addr, SocketAddr, SocketAddrV4};
+    use std::time::{Duration, UNIX_EPOCH};
+
+    const RIP_PAYLOAD: [u8; 24] = [
+        2, 2, 0, 0, //
+        0, 2, 1, 2, //
+        192, 0, 2, 100, //
+        255, 255, 255, 0, //
+        192, 0, 2, 111, //
+        0, 0, 0, 1, //
+    ];
+
+    fn ipv4_udp(source_port: u16, destination_port: u16, payload: &[u8]) -> Vec<u8> {
+        let total_length = (20 + 8 + payload.len()) as u16;
+        let udp_length = (8 + payload.len()) as u16;
+        let mut bytes = vec![0x45, 0];
+        bytes.extend(total_length.to_be_bytes());
+        bytes.extend([0, 0, 0, 0, 1, 17, 0, 0]);
+        bytes.extend([192, 0, 2, 1, 224, 0, 0, 9]);
+        bytes.extend(source_port.to_be_bytes());
+        bytes.extend(destination_port.to_be_bytes());
+        bytes.extend(udp_length.to_be_bytes());
+        bytes.extend([0, 0]);
+        bytes.extend(payload);
+        bytes
+    }
+
+    fn ethernet(ethertype: &[u8], payload: &[u8]) -> Vec<u8> {
+        let mut bytes = vec![0x01, 0x00, 0x5e, 0, 0, 9, 0x02, 0, 0, 0, 0, 1];
+        bytes.extend(ethertype);
+        bytes.extend(payload);
+        bytes
+    }
+
+    fn pcap(link_type: u32, frames: &[Vec<u8>]) -> Vec<u8> {
+        let mut bytes = vec![];
+        bytes.extend(0xa1b2c3d4u32.to_le_bytes());
+        bytes.extend(2u16.to_le_bytes());
+        bytes.extend(4u16.to_le_bytes());
+        bytes.extend([0; 8]);
+        bytes.extend(65535u32.to_le_bytes());
+        bytes.extend(link_type.to_le_bytes());
+        for (i, frame) in frames.iter().enumerate() {
+            bytes.extend((1_700_000_000 + i as u32).to_le_bytes());
+            bytes.extend(250_000u32.to_le_bytes());
+            bytes.extend((frame.len() as u32).to_le_bytes());
+            bytes.extend((frame.len() as u32).to_le_bytes());
+            bytes.extend(frame);
+        }
+        bytes
+    }
+
+    fn pcapng_block(block_type: u32, body: &[u8]) -> Vec<u8> {
+        let padded = body.len().div_ceil(4) * 4;
+        let total_length = (12 + padded) as u32;
+        let mut bytes = vec![];
+        bytes.extend(block_type.to_be_bytes());
+        bytes.extend(total_length.to_be_bytes());
+        bytes.extend(body);
+        bytes.extend(vec![0; padded - body.len()]);
+        bytes.extend(total_length.to_be_bytes());
+        bytes
+    }
+
+    #[test]
+    fn test_read_pcap() {
+        let mut padded = ethernet(&[0x08, 0x00], &ipv4_udp(520, 520, &RIP_PAYLOAD));
+        padded.extend([0; 4]); // the Ethernet padding must be ignored
+        let frames = vec![
+            ethernet(&[0x08, 0x00], &ipv4_udp(53, 53, &[0; 12])),
+            padded,
+            ethernet(
+                &[0x81, 0x00, 0x00, 0x0a, 0x08, 0x00],
+                &ipv4_udp(520, 520, &RIP_PAYLOAD[..23]),
+            ),
+        ];
+        let bytes = pcap(LINKTYPE_ETHERNET, &frames);
+        let mut reader = Reader::new(bytes.as_slice()).unwrap();
+
+        let capture = reader.next().unwrap().unwrap();
+        assert_eq!(capture.get_frame_number(), 2);
+        assert_eq!(
+            capture.get_timestamp(),
+            UNIX_EPOCH + Duration::new(1_700_000_001, 250_000_000)
+        );
+        assert_eq!(
+            capture.get_source(),
+            SocketAddr::new(Ipv4Addr::new(192, 0, 2, 1).into(), 520)
+        );
+        assert_eq!(
+            capture.get_destination(),
+            SocketAddr::new(Ipv4Addr::new(224, 0, 0, 9).into(), 520)
+        );
+        assert_eq!(capture.get_payload(), RIP_PAYLOAD);
+        let (_, _, _, message, _) = capture.into_parts();
+        match message {
+            ParsedMessage::V2(message) => {
+                assert_eq!(message.get_command(), command::Kind::Response);
+                assert_eq!(message.get_entries().len(), 1);
+            }
+            _ => panic!("unexpected version"),
+        }
+
+        assert!(matches!(
+            reader.next().unwrap(),
+            Err(PcapError::InvalidRipPacket(
+                ParseError::TrailingBytes(19, 4),
+                3
+            ))
+        ));
+        assert!(reader.next().is_none());
+    }
+
+    #[test]
+    fn test_read_pcapng() {
+        let mut section_header = vec![];
+        section_header.extend(0x1a2b3c4du32.to_be_bytes());
+        section_header.extend([0, 1, 0, 0]);
+        section_header.extend(u64::MAX.to_be_bytes());
+
+        let mut interface = vec![];
+        interface.extend((LINKTYPE_LINUX_SLL as u16).to_be_bytes());
+        interface.extend([0, 0, 0, 0, 0xff, 0xff]);
+        interface.extend([0, 9, 0, 1, 9, 0, 0, 0]); // if_tsresol: nanoseconds
+        interface.extend([0, 0, 0, 0]);
+
+        let mut sll = vec![0, 0, 0, 1, 0, 6, 2, 0, 0, 0, 0, 1, 0, 0, 0x08, 0x00];
+        sll.extend(ipv4_udp(520, 520, &RIP_PAYLOAD));
+        let mut packet = vec![];
+        packet.extend(0u32.to_be_bytes());
+        packet.extend(1_700_000_000_123_456_789u64.to_be_bytes());
+        packet.extend((sll.len() as u32).to_be_bytes());
+        packet.extend((sll.len() as u32).to_be_bytes());
+        packet.extend(&sll);
+
+        let mut bytes = pcapng_block(0x0a0d0d0a, &section_header);
+        bytes.extend(pcapng_block(1, &interface));
+        bytes.extend(pcapng_block(5, &[0; 8])); // an interface statistics block is skipped
+        bytes.extend(pcapng_block(6, &packet));
+
+        let mut reader = Reader::new(bytes.as_slice()).unwrap();
+        let capture = reader.next().unwrap().unwrap();
+        assert_eq!(capture.get_frame_number(), 1);
+        assert_eq!(
+            capture.get_timestamp(),
+            UNIX_EPOCH + Duration::new(1_700_000_000, 123_456_789)
+        );
+        assert_eq!(
+            capture.get_message().get_version(),
+            version::Version::Version2
+        );
+        assert!(reader.next().is_none());
+    }
+
+    #[test]
+    fn test_read_out_of_range_timestamp() {
+        let mut section_header = vec![];
+        section_header.extend(0x1a2b3c4du32.to_be_bytes());
+        section_header.extend([0, 1, 0, 0]);
+        section_header.extend(u64::MAX.to_be_bytes());
+
+        let mut interface = vec![];
+        interface.extend((LINKTYPE_ETHERNET as u16).to_be_bytes());
+        interface.extend([0, 0, 0, 0, 0xff, 0xff]);
+        interface.extend([0, 9, 0, 1, 0, 0, 0, 0]); // if_tsresol: seconds
+        interface.extend([0, 0, 0, 0]);
+
+        let frame = ethernet(&[0x08, 0x00], &ipv4_udp(520, 520, &RIP_PAYLOAD));
+        let mut packet = vec![];
+        packet.extend(0u32.to_be_bytes());
+        packet.extend(u64::MAX.to_be_bytes());
+        packet.extend((frame.len() as u32).to_be_bytes());
+        packet.extend((frame.len() as u32).to_be_bytes());
+        packet.extend(&frame);
+
+        let mut bytes = pcapng_block(0x0a0d0d0a, &section_header);
+        bytes.extend(pcapng_block(1, &interface));
+        bytes.extend(pcapng_block(6, &packet));
+
+        let mut reader = Reader::new(bytes.as_slice()).unwrap();
+        assert!(matches!(
+            reader.next(),
+            Some(Err(PcapError::MalformedFrame("timestamp out of range", 1)))
+        ));
+    }
+
+    #[test]
+    fn test_read_errors() {
+        assert!(matches!(
+            Reader::new(vec![0, 1, 2, 3].as_slice()),
+            Err(PcapError::UnknownFormat(0x00010203))
+        ));
+
+        let ipv6_ripng = {
+            let mut bytes = vec![0x60, 0, 0, 0, 0, 12, 17, 255];
+            bytes.extend([0xfe, 0x80, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1]);
+            bytes.extend([0xff, 0x02, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 9]);
+            bytes.extend([2, 9, 2, 9, 0, 12, 0, 0, 2, 1, 0, 0]);
+            bytes
+        };
+        let frames = vec![
+            ethernet(&[0x86, 0xdd], &ipv6_ripng),
+            ethernet(&[0x08, 0x00], &ipv4_udp(520, 520, &RIP_PAYLOAD)[..30]),
+        ];
+        let mut bytes = pcap(LINKTYPE_ETHERNET, &frames);
+        bytes.extend([0; 3]); // a truncated record header
+
+        let mut reader = Reader::new(bytes.as_slice()).unwrap();
+        assert!(matches!(
+            reader.next().unwrap(),
+            Err(PcapError::MalformedFrame("truncated IPv4 packet", 2))
+        ));
+        assert!(matches!(
+            reader.next().unwrap(),
+            Err(PcapError::CorruptFile(_))
+        ));
+        assert!(reader.next().is_none());
+
+        assert_eq!(decode_frame(147, &[]), Err(FrameError::UnsupportedLinkType));
+    }
+
+    #[test]
+    fn test_write_and_read() {
+        let packet = || {
+            Packet::make_v2_packet(
+                Header::new(command::Kind::Response, version::Version::Version2),
+                vec![v2::Entry::new(
+                    address_family::Identifier::IP,
+                    RouteTag::new(0),
+                    Ipv4Addr::new(10, 0, 0, 0),
+                    Ipv4Addr::new(255, 0, 0, 0),
+                    Ipv4Addr::new(0, 0, 0, 0),
+                    Metric::from_raw(2),
+                )],
+            )
+            .unwrap()
+        };
+
+        let mut writer = Writer::new(vec![]).unwrap();
+        let options = FrameOptions::new()
+            .source(SocketAddrV4::new(Ipv4Addr::new(192, 0, 2, 2), 520))
+            .timestamp(UNIX_EPOCH + Duration::new(1_700_000_000, 500_000_000));
+        assert_eq!(
+            writer.write_v2_packet(packet(), &options).unwrap(),
+            14 + 20 + 8 + 24
+        );
+        let unicast = options
+            .destination(SocketAddrV4::new(Ipv4Addr::new(192, 0, 2, 3), 520))
+            .destination_mac([0x02, 0, 0, 0, 0, 0x03]);
+        writer.write_v2_packet(packet(), &unicast).unwrap();
+        let bytes = writer.into_inner();
+
+        let frame = &bytes[24 + 16..24 + 16 + 66];
+        assert_eq!(frame[0..6], [0x01, 0x00, 0x5e, 0x00, 0x00, 0x09]);
+        assert_eq!(frame[6..12], [0x02, 0, 0, 0, 0, 0x01]);
+        assert_eq!(frame[14 + 8], 1); // TTL
+        assert_eq!(checksum(&[&frame[14..34]]), 0);
+        let mut pseudo_header = vec![];
+        pseudo_header.extend(&frame[26..34]);
+        pseudo_header.extend([0, 17, 0, 32]);
+        assert_eq!(checksum(&[&pseudo_header, &frame[34..]]), 0);
+
+        let mut reader = Reader::new(bytes.as_slice()).unwrap();
+        let capture = reader.next().unwrap().unwrap();
+        assert_eq!(
+            capture.get_timestamp(),
+            UNIX_EPOCH + Duration::new(1_700_000_000, 500_000_000)
+        );
+        assert_eq!(
+            capture.get_source(),
+            SocketAddr::new(Ipv4Addr::new(192, 0, 2, 2).into(), 520)
+        );
+        assert_eq!(
+            capture.into_parts().3,
+            ParsedMessage::V2(RipMessage::Response(packet().into_entries()))
+        );
+
+        let capture = reader.next().unwrap().unwrap();
+        assert_eq!(
+            capture.get_destination(),
+            SocketAddr::new(Ipv4Addr::new(192, 0, 2, 3).into(), 520)
+        );
+        assert!(reader.next().is_none());
+        assert_eq!(bytes[24 + 16 + 66 + 16..][0..6], [0x02, 0, 0, 0, 0, 0x03]);
+    }
+
+    #[test]
+    fn test_destination_mac() {
+        let options = FrameOptions::new();
+        assert_eq!(
+            options.get_destination_mac(),
+            [0x01, 0x00, 0x5e, 0x00, 0x00, 0x09]
+        );
+        assert_eq!(
+            options
+                .destination(SocketAddrV4::new(Ipv4Addr::new(239, 129, 2, 3), 520))
+                .get_destination_mac(),
+            [0x01, 0x00, 0x5e, 0x01, 0x02, 0x03]
+        );
+        assert_eq!(
+            options
+                .destination(SocketAddrV4::new(Ipv4Addr::BROADCAST, 520))
+                .get_destination_mac(),
+            [0xff; 6]
+        );
+    }
+}
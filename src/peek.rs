@@ -0,0 +1,119 @@
+use crate::address_family;
+use crate::header::{self, Header};
+use crate::packet::{ENTRY_LENGTH, HEADER_LENGTH, MAX_PACKET_LENGTH};
+use crate::parser::ParseError;
+use crate::{command, version};
+
+/// Summary is the lightweight summary of a RIP packet that [`peek`] returns.
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub struct Summary {
+    header: Header,
+    num_of_entries: usize,
+    authentication: bool,
+}
+
+impl Summary {
+    pub fn get_header(&self) -> &Header {
+        &self.header
+    }
+
+    pub fn get_command(&self) -> command::Kind {
+        self.header.get_command()
+    }
+
+    pub fn get_version(&self) -> version::Version {
+        self.header.get_version()
+    }
+
+    /// get_num_of_entries returns the number of the entries, including the authentication entry.
+    pub fn get_num_of_entries(&self) -> usize {
+        self.num_of_entries
+    }
+
+    /// has_authentication returns whether the first entry is an authentication entry.
+    pub fn has_authentication(&self) -> bool {
+        self.authentication
+    }
+}
+
+/// peek reads only the header and the length of the packet, and classifies the packet without decoding the entries.
+/// The packet must consist of the header and whole entries, and must be up to MAX_PACKET_LENGTH bytes.
+pub fn peek(bytes: &[u8]) -> Result<Summary, ParseError> {
+    let (header, cursor) = header::parse(0, bytes)?;
+
+    if bytes.len() > MAX_PACKET_LENGTH {
+        return Err(ParseError::PacketTooLong(bytes.len(), MAX_PACKET_LENGTH));
+    }
+
+    let trailing = (bytes.len() - cursor) % ENTRY_LENGTH;
+    if trailing != 0 {
+        return Err(ParseError::TrailingBytes(trailing, bytes.len() - trailing));
+    }
+
+    let afi = bytes
+        .get(HEADER_LENGTH..HEADER_LENGTH + 2)
+        .map(|b| address_family::Identifier::from_u16(u16::from_be_bytes([b[0], b[1]])));
+
+    Ok(Summary {
+        header,
+        num_of_entries: (bytes.len() - cursor) / ENTRY_LENGTH,
+        authentication: afi == Some(address_family::Identifier::AuthenticationPresent),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::ParseError;
+    use crate::peek::peek;
+    use crate::{command, version};
+
+    #[test]
+    fn test_peek() {
+        let summary = peek(
+            vec![
+                2, 2, 0, 0, //
+                0xff, 0xff, 0, 2, //
+                b'p', b'a', b's', b's', //
+                b'w', b'o', b'r', b'd', //
+                0, 0, 0, 0, //
+                0, 0, 0, 0, //
+                0, 2, 0, 0, //
+                192, 0, 2, 0, //
+                255, 255, 255, 0, //
+                0, 0, 0, 0, //
+                0, 0, 0, 1, //
+            ]
+            .as_slice(),
+        )
+        .unwrap();
+
+        assert_eq!(summary.get_command(), command::Kind::Response);
+        assert_eq!(summary.get_version(), version::Version::Version2);
+        assert_eq!(summary.get_num_of_entries(), 2);
+        assert!(summary.has_authentication());
+
+        let summary = peek(vec![1, 1, 0, 0].as_slice()).unwrap();
+        assert_eq!(summary.get_command(), command::Kind::Request);
+        assert_eq!(summary.get_num_of_entries(), 0);
+        assert!(!summary.has_authentication());
+    }
+
+    #[test]
+    fn test_peek_invalid_length() {
+        assert_eq!(
+            peek(vec![2, 2, 0, 0, 0, 2, 0].as_slice()).unwrap_err(),
+            ParseError::TrailingBytes(3, 4)
+        );
+        assert_eq!(
+            peek(vec![2, 2, 0].as_slice()).unwrap_err(),
+            ParseError::InsufficientInputBytesLength(3)
+        );
+
+        let mut bytes = vec![2, 2, 0, 0];
+        bytes.extend(vec![0; 20 * 26]);
+        assert_eq!(
+            peek(bytes.as_slice()).unwrap_err(),
+            ParseError::PacketTooLong(524, 504)
+        );
+    }
+}
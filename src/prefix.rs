@@ -0,0 +1,177 @@
+use std::fmt;
+use std::net::Ipv4Addr;
+use std::str::FromStr;
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq, Clone)]
+pub enum PrefixError {
+    #[error("prefix length {0} exceeds 32")]
+    InvalidPrefixLength(u8),
+    #[error("prefix {0}/{1} is not canonical; the host bits must be zero")]
+    NonCanonicalPrefix(Ipv4Addr, u8),
+    #[error("subnet mask {0} is not contiguous")]
+    NonContiguousSubnetMask(Ipv4Addr),
+    #[error("invalid prefix notation \"{0}\"")]
+    InvalidNotation(String),
+}
+
+/// Ipv4Prefix is a canonical IPv4 prefix such as 192.0.2.0/24; that is, the host bits of the address are zero.
+#[derive(PartialEq, Eq, Hash, Copy, Clone, Debug)]
+pub struct Ipv4Prefix {
+    address: Ipv4Addr,
+    length: u8,
+}
+
+impl Ipv4Prefix {
+    pub fn new(address: Ipv4Addr, length: u8) -> Result<Self, PrefixError> {
+        if length > 32 {
+            return Err(PrefixError::InvalidPrefixLength(length));
+        }
+        if u32::from(address) & !length_to_mask(length) != 0 {
+            return Err(PrefixError::NonCanonicalPrefix(address, length));
+        }
+        Ok(Ipv4Prefix { address, length })
+    }
+
+    pub fn from_address_and_mask(
+        address: Ipv4Addr,
+        subnet_mask: Ipv4Addr,
+    ) -> Result<Self, PrefixError> {
+        if !is_contiguous_mask(subnet_mask) {
+            return Err(PrefixError::NonContiguousSubnetMask(subnet_mask));
+        }
+        Ipv4Prefix::new(address, u32::from(subnet_mask).leading_ones() as u8)
+    }
+
+    pub fn get_address(&self) -> Ipv4Addr {
+        self.address
+    }
+
+    pub fn get_length(&self) -> u8 {
+        self.length
+    }
+
+    pub fn get_subnet_mask(&self) -> Ipv4Addr {
+        Ipv4Addr::from(length_to_mask(self.length))
+    }
+
+    pub fn contains(&self, address: Ipv4Addr) -> bool {
+        u32::from(address) & length_to_mask(self.length) == u32::from(self.address)
+    }
+}
+
+impl fmt::Display for Ipv4Prefix {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.address, self.length)
+    }
+}
+
+impl FromStr for Ipv4Prefix {
+    type Err = PrefixError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (address, length) = s
+            .split_once('/')
+            .ok_or_else(|| PrefixError::InvalidNotation(s.to_string()))?;
+        let address =
+            Ipv4Addr::from_str(address).map_err(|_| PrefixError::InvalidNotation(s.to_string()))?;
+        let length =
+            u8::from_str(length).map_err(|_| PrefixError::InvalidNotation(s.to_string()))?;
+        Ipv4Prefix::new(address, length)
+    }
+}
+
+/// is_contiguous_mask returns whether the subnet mask consists of leading ones followed by trailing zeros.
+pub(crate) fn is_contiguous_mask(subnet_mask: Ipv4Addr) -> bool {
+    let mask = u32::from(subnet_mask);
+    mask.leading_ones() + mask.trailing_zeros() == 32
+}
+
+fn length_to_mask(length: u8) -> u32 {
+    match length {
+        0 => 0,
+        _ => u32::MAX << (32 - length as u32),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prefix::{Ipv4Prefix, PrefixError};
+    use std::net::Ipv4Addr;
+
+    #[test]
+    fn test_new() {
+        let prefix = Ipv4Prefix::new(Ipv4Addr::new(192, 0, 2, 0), 24).unwrap();
+        assert_eq!(prefix.get_address(), Ipv4Addr::new(192, 0, 2, 0));
+        assert_eq!(prefix.get_length(), 24);
+        assert_eq!(prefix.get_subnet_mask(), Ipv4Addr::new(255, 255, 255, 0));
+
+        let default_route = Ipv4Prefix::new(Ipv4Addr::new(0, 0, 0, 0), 0).unwrap();
+        assert_eq!(default_route.get_subnet_mask(), Ipv4Addr::new(0, 0, 0, 0));
+
+        let host_route = Ipv4Prefix::new(Ipv4Addr::new(192, 0, 2, 1), 32).unwrap();
+        assert_eq!(
+            host_route.get_subnet_mask(),
+            Ipv4Addr::new(255, 255, 255, 255)
+        );
+
+        assert_eq!(
+            Ipv4Prefix::new(Ipv4Addr::new(192, 0, 2, 1), 24).unwrap_err(),
+            PrefixError::NonCanonicalPrefix(Ipv4Addr::new(192, 0, 2, 1), 24)
+        );
+        assert_eq!(
+            Ipv4Prefix::new(Ipv4Addr::new(192, 0, 2, 0), 33).unwrap_err(),
+            PrefixError::InvalidPrefixLength(33)
+        );
+    }
+
+    #[test]
+    fn test_from_address_and_mask() {
+        assert_eq!(
+            Ipv4Prefix::from_address_and_mask(
+                Ipv4Addr::new(10, 0, 0, 0),
+                Ipv4Addr::new(255, 0, 0, 0)
+            )
+            .unwrap(),
+            Ipv4Prefix::new(Ipv4Addr::new(10, 0, 0, 0), 8).unwrap()
+        );
+        assert_eq!(
+            Ipv4Prefix::from_address_and_mask(
+                Ipv4Addr::new(10, 0, 0, 0),
+                Ipv4Addr::new(255, 0, 255, 0)
+            )
+            .unwrap_err(),
+            PrefixError::NonContiguousSubnetMask(Ipv4Addr::new(255, 0, 255, 0))
+        );
+    }
+
+    #[test]
+    fn test_from_str_and_to_string() {
+        let prefix: Ipv4Prefix = "192.0.2.0/24".parse().unwrap();
+        assert_eq!(
+            prefix,
+            Ipv4Prefix::new(Ipv4Addr::new(192, 0, 2, 0), 24).unwrap()
+        );
+        assert_eq!(prefix.to_string(), "192.0.2.0/24");
+
+        assert_eq!(
+            "192.0.2.0".parse::<Ipv4Prefix>().unwrap_err(),
+            PrefixError::InvalidNotation("192.0.2.0".to_string())
+        );
+        assert_eq!(
+            "192.0.2/24".parse::<Ipv4Prefix>().unwrap_err(),
+            PrefixError::InvalidNotation("192.0.2/24".to_string())
+        );
+        assert_eq!(
+            "192.0.2.1/24".parse::<Ipv4Prefix>().unwrap_err(),
+            PrefixError::NonCanonicalPrefix(Ipv4Addr::new(192, 0, 2, 1), 24)
+        );
+    }
+
+    #[test]
+    fn test_contains() {
+        let prefix: Ipv4Prefix = "192.0.2.0/24".parse().unwrap();
+        assert!(prefix.contains(Ipv4Addr::new(192, 0, 2, 255)));
+        assert!(!prefix.contains(Ipv4Addr::new(192, 0, 3, 0)));
+    }
+}
@@ -1,18 +1,324 @@
-use crate::parser::Parsed;
-use crate::serializer::SerializeError;
+use crate::parser::{Parsable, Parsed};
+use crate::serializer::{write_slice, Serializable, SerializeError};
 use crate::{byte_reader, parser::ParseError};
+use std::fmt;
+use std::ops::RangeInclusive;
+use std::str::FromStr;
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq, Clone)]
+pub enum RouteTagError {
+    #[error("the number of policy bits {0} exceeds 16")]
+    InvalidPolicyBits(u8),
+    #[error("AS number {0} exceeds the maximum {1} of the tag layout")]
+    AsNumberOutOfRange(u16, u16),
+    #[error("policy {0} exceeds the maximum {1} of the tag layout")]
+    PolicyOutOfRange(u16, u16),
+    #[error("invalid route tag notation \"{0}\"")]
+    InvalidNotation(String),
+}
+
+/// RouteTag is the attribute that is assigned to a route to distinguish the internal routes from the external ones.
+/// RFC2453 suggests to carry the autonomous system number of EGP/BGP-learned routes in it; see [`TagLayout`].
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Copy, Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+pub struct RouteTag(u16);
+
+impl RouteTag {
+    pub fn new(value: u16) -> Self {
+        RouteTag(value)
+    }
+
+    pub fn to_u16(&self) -> u16 {
+        self.0
+    }
+
+    /// matches_masked returns whether the bits of the tag that are set in the mask equal those of the value.
+    pub fn matches_masked(&self, value: RouteTag, mask: u16) -> bool {
+        self.0 & mask == value.0 & mask
+    }
+}
+
+impl From<u16> for RouteTag {
+    fn from(value: u16) -> Self {
+        RouteTag(value)
+    }
+}
+
+impl From<RouteTag> for u16 {
+    fn from(route_tag: RouteTag) -> Self {
+        route_tag.0
+    }
+}
+
+impl fmt::Display for RouteTag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl fmt::LowerHex for RouteTag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::LowerHex::fmt(&self.0, f)
+    }
+}
+
+impl FromStr for RouteTag {
+    type Err = RouteTagError;
+
+    /// from_str accepts a decimal number (e.g. `258`) or a hexadecimal one with the `0x` prefix (e.g. `0x0102`).
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let value = match s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+            Some(hex) => u16::from_str_radix(hex, 16),
+            None => u16::from_str(s),
+        };
+        value
+            .map(RouteTag)
+            .map_err(|_| RouteTagError::InvalidNotation(s.to_string()))
+    }
+}
+
+/// TagLayout describes how a route tag carries an AS number and policy bits:
+/// the upper `policy_bits` bits hold the policy and the remaining lower bits hold the AS number.
+#[derive(PartialEq, Eq, Hash, Copy, Clone, Debug)]
+pub struct TagLayout {
+    policy_bits: u8,
+}
+
+impl TagLayout {
+    pub fn new(policy_bits: u8) -> Result<Self, RouteTagError> {
+        if policy_bits > 16 {
+            return Err(RouteTagError::InvalidPolicyBits(policy_bits));
+        }
+        Ok(TagLayout { policy_bits })
+    }
+
+    pub fn get_policy_bits(&self) -> u8 {
+        self.policy_bits
+    }
+
+    pub fn max_as_number(&self) -> u16 {
+        low_bits_mask(16 - self.policy_bits)
+    }
+
+    pub fn max_policy(&self) -> u16 {
+        low_bits_mask(self.policy_bits)
+    }
+
+    /// encode packs the AS number and the policy into a route tag.
+    pub fn encode(&self, as_number: u16, policy: u16) -> Result<RouteTag, RouteTagError> {
+        if as_number > self.max_as_number() {
+            return Err(RouteTagError::AsNumberOutOfRange(
+                as_number,
+                self.max_as_number(),
+            ));
+        }
+        if policy > self.max_policy() {
+            return Err(RouteTagError::PolicyOutOfRange(policy, self.max_policy()));
+        }
+        Ok(RouteTag(self.shift_policy(policy) | as_number))
+    }
+
+    /// decode unpacks the route tag into the AS number and the policy.
+    pub fn decode(&self, route_tag: RouteTag) -> (u16, u16) {
+        (self.get_as_number(route_tag), self.get_policy(route_tag))
+    }
+
+    pub fn get_as_number(&self, route_tag: RouteTag) -> u16 {
+        route_tag.0 & self.max_as_number()
+    }
+
+    pub fn get_policy(&self, route_tag: RouteTag) -> u16 {
+        match self.policy_bits {
+            0 => 0,
+            bits => route_tag.0 >> (16 - bits as u32),
+        }
+    }
+
+    /// as_number_mask returns the mask that selects the AS number bits; it is for [`TagFilter::Masked`].
+    pub fn as_number_mask(&self) -> u16 {
+        self.max_as_number()
+    }
+
+    /// policy_mask returns the mask that selects the policy bits; it is for [`TagFilter::Masked`].
+    pub fn policy_mask(&self) -> u16 {
+        self.shift_policy(self.max_policy())
+    }
+
+    /// format renders the route tag as `<AS number>:<policy>`.
+    pub fn format(&self, route_tag: RouteTag) -> String {
+        let (as_number, policy) = self.decode(route_tag);
+        format!("{}:{}", as_number, policy)
+    }
+
+    /// parse reads the route tag from the `<AS number>:<policy>` notation that [`TagLayout::format`] renders.
+    pub fn parse(&self, s: &str) -> Result<RouteTag, RouteTagError> {
+        let (as_number, policy) = s
+            .split_once(':')
+            .ok_or_else(|| RouteTagError::InvalidNotation(s.to_string()))?;
+        let as_number =
+            u16::from_str(as_number).map_err(|_| RouteTagError::InvalidNotation(s.to_string()))?;
+        let policy =
+            u16::from_str(policy).map_err(|_| RouteTagError::InvalidNotation(s.to_string()))?;
+        self.encode(as_number, policy)
+    }
+
+    fn shift_policy(&self, policy: u16) -> u16 {
+        match self.policy_bits {
+            0 => 0,
+            bits => policy << (16 - bits as u32),
+        }
+    }
+}
+
+/// TagFilter is a condition on a route tag for route filters and redistribution.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub enum TagFilter {
+    Any,
+    Exact(RouteTag),
+    Masked { value: RouteTag, mask: u16 },
+    Range(RangeInclusive<RouteTag>),
+}
+
+impl TagFilter {
+    pub fn matches(&self, route_tag: RouteTag) -> bool {
+        match self {
+            TagFilter::Any => true,
+            TagFilter::Exact(value) => route_tag == *value,
+            TagFilter::Masked { value, mask } => route_tag.matches_masked(*value, *mask),
+            TagFilter::Range(range) => range.contains(&route_tag),
+        }
+    }
+}
+
+fn low_bits_mask(bits: u8) -> u16 {
+    match bits {
+        0 => 0,
+        _ => u16::MAX >> (16 - bits as u32),
+    }
+}
 
-pub type RouteTag = u16;
 pub(crate) fn parse(cursor: usize, bytes: &[u8]) -> Result<Parsed<RouteTag>, ParseError> {
     let (route_tag_first_byte, cursor) = byte_reader::read(cursor, bytes)?;
     let (route_tag_second_byte, cursor) = byte_reader::read(cursor, bytes)?;
 
     Ok((
-        ((route_tag_first_byte as RouteTag) << 8) + route_tag_second_byte as RouteTag,
+        RouteTag(((route_tag_first_byte as u16) << 8) + route_tag_second_byte as u16),
         cursor,
     ))
 }
 
-pub(crate) fn to_bytes(value: RouteTag) -> Result<Vec<u8>, SerializeError> {
-    Ok(vec![((value & 0xff00) >> 8) as u8, (value & 0x00ff) as u8])
+pub(crate) fn write_bytes(route_tag: RouteTag, buf: &mut [u8]) -> Result<usize, SerializeError> {
+    let value = route_tag.to_u16();
+    write_slice(
+        &[((value & 0xff00) >> 8) as u8, (value & 0x00ff) as u8],
+        buf,
+    )
+}
+
+impl Parsable for RouteTag {
+    fn parse(cursor: usize, bytes: &[u8]) -> Result<Parsed<RouteTag>, ParseError> {
+        parse(cursor, bytes)
+    }
+}
+
+impl Serializable for RouteTag {
+    fn serialized_len(&self) -> usize {
+        2
+    }
+
+    fn write_bytes(&self, buf: &mut [u8]) -> Result<usize, SerializeError> {
+        write_bytes(*self, buf)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::Parsable;
+    use crate::route_tag::{RouteTag, RouteTagError, TagFilter, TagLayout};
+    use crate::serializer::Serializable;
+
+    #[test]
+    fn test_encode_and_decode_with_layout() {
+        let layout = TagLayout::new(4).unwrap();
+        assert_eq!(layout.max_as_number(), 0x0fff);
+        assert_eq!(layout.max_policy(), 0x000f);
+
+        let route_tag = layout.encode(3000, 0b1010).unwrap();
+        assert_eq!(route_tag, RouteTag::new(0xabb8));
+        assert_eq!(layout.decode(route_tag), (3000, 0b1010));
+        assert_eq!(layout.format(route_tag), "3000:10");
+        assert_eq!(layout.parse("3000:10").unwrap(), route_tag);
+
+        assert_eq!(
+            layout.encode(4096, 0).unwrap_err(),
+            RouteTagError::AsNumberOutOfRange(4096, 4095)
+        );
+        assert_eq!(
+            layout.encode(1, 16).unwrap_err(),
+            RouteTagError::PolicyOutOfRange(16, 15)
+        );
+        assert_eq!(
+            layout.parse("3000").unwrap_err(),
+            RouteTagError::InvalidNotation("3000".to_string())
+        );
+        assert_eq!(
+            TagLayout::new(17).unwrap_err(),
+            RouteTagError::InvalidPolicyBits(17)
+        );
+    }
+
+    #[test]
+    fn test_layout_edges() {
+        let as_only = TagLayout::new(0).unwrap();
+        assert_eq!(as_only.encode(65535, 0).unwrap(), RouteTag::new(65535));
+        assert_eq!(as_only.decode(RouteTag::new(65535)), (65535, 0));
+
+        let policy_only = TagLayout::new(16).unwrap();
+        assert_eq!(policy_only.encode(0, 258).unwrap(), RouteTag::new(258));
+        assert_eq!(policy_only.decode(RouteTag::new(258)), (0, 258));
+    }
+
+    #[test]
+    fn test_filter() {
+        let layout = TagLayout::new(4).unwrap();
+        let route_tag = layout.encode(3000, 2).unwrap();
+
+        assert!(TagFilter::Any.matches(route_tag));
+        assert!(TagFilter::Exact(route_tag).matches(route_tag));
+        assert!(!TagFilter::Exact(RouteTag::new(0)).matches(route_tag));
+        assert!(TagFilter::Masked {
+            value: layout.encode(3000, 0).unwrap(),
+            mask: layout.as_number_mask(),
+        }
+        .matches(route_tag));
+        assert!(!TagFilter::Masked {
+            value: layout.encode(0, 1).unwrap(),
+            mask: layout.policy_mask(),
+        }
+        .matches(route_tag));
+        assert!(TagFilter::Range(RouteTag::new(0x2000)..=RouteTag::new(0x2fff)).matches(route_tag));
+        assert!(!TagFilter::Range(RouteTag::new(0)..=RouteTag::new(100)).matches(route_tag));
+    }
+
+    #[test]
+    fn test_from_str_and_format() {
+        assert_eq!("258".parse::<RouteTag>().unwrap(), RouteTag::new(258));
+        assert_eq!("0x0102".parse::<RouteTag>().unwrap(), RouteTag::new(258));
+        assert_eq!(
+            "65536".parse::<RouteTag>().unwrap_err(),
+            RouteTagError::InvalidNotation("65536".to_string())
+        );
+        assert_eq!(RouteTag::new(258).to_string(), "258");
+        assert_eq!(format!("{:#06x}", RouteTag::new(258)), "0x0102");
+    }
+
+    #[test]
+    fn test_parse_and_write_bytes() {
+        let (route_tag, cursor) = RouteTag::parse(0, vec![1, 2].as_slice()).unwrap();
+        assert_eq!(route_tag, RouteTag::new(258));
+        assert_eq!(cursor, 2);
+        assert_eq!(route_tag.to_bytes().unwrap(), vec![1, 2]);
+    }
 }
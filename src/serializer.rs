@@ -1,20 +1,49 @@
-use crate::packet::Packet;
+use crate::message::{ParsedMessage, RipMessage};
+use crate::packet::{Packet, MAX_DATAGRAM_LENGTH};
 use crate::v1;
 use crate::v2;
+use std::io;
 use thiserror::Error;
 
 #[derive(Error, Debug, PartialEq)]
 pub enum SerializeError {
-    #[error("encountered the unknown command kind")]
-    UnknownCommandKind,
-    #[error("encountered the unknown version")]
-    UnknownVersion,
-    #[error("encountered the unknown address family identifier")]
-    UnknownAddressFamilyIdentifier,
+    #[error(
+        "insufficient buffer length; {0} bytes are required but the buffer has only {1} bytes"
+    )]
+    InsufficientBufferLength(usize, usize),
+    #[error("the number of RIP entries {0} exceeds the maximum number {1}")]
+    MaxRIPEntriesNumberExceeded(usize, usize),
 }
 
-pub(crate) trait Serializable {
-    fn to_bytes(&self) -> Result<Vec<u8>, SerializeError>;
+/// Serializable is a trait for the wire types that can be encoded into the bytes.
+pub trait Serializable {
+    /// serialized_len returns the number of bytes that the serialized value occupies.
+    fn serialized_len(&self) -> usize;
+
+    /// write_bytes writes the serialized value into the head of the given buffer and returns the number of the written bytes.
+    fn write_bytes(&self, buf: &mut [u8]) -> Result<usize, SerializeError>;
+
+    fn to_bytes(&self) -> Result<Vec<u8>, SerializeError> {
+        let mut bytes = vec![0; self.serialized_len()];
+        self.write_bytes(&mut bytes)?;
+        Ok(bytes)
+    }
+}
+
+pub(crate) fn check_buffer_length(buf: &[u8], required: usize) -> Result<(), SerializeError> {
+    if buf.len() < required {
+        return Err(SerializeError::InsufficientBufferLength(
+            required,
+            buf.len(),
+        ));
+    }
+    Ok(())
+}
+
+pub(crate) fn write_slice(src: &[u8], buf: &mut [u8]) -> Result<usize, SerializeError> {
+    check_buffer_length(buf, src.len())?;
+    buf[..src.len()].copy_from_slice(src);
+    Ok(src.len())
 }
 
 pub fn serialize_v1_packet(packet: Packet<v1::Entry>) -> Result<Vec<u8>, SerializeError> {
@@ -25,14 +54,169 @@ pub fn serialize_v2_packet(packet: Packet<v2::Entry>) -> Result<Vec<u8>, Seriali
     packet.to_bytes()
 }
 
+pub fn serialize_v1_message(message: RipMessage<v1::Entry>) -> Result<Vec<u8>, SerializeError> {
+    message.to_bytes()
+}
+
+pub fn serialize_v2_message(message: RipMessage<v2::Entry>) -> Result<Vec<u8>, SerializeError> {
+    message.to_bytes()
+}
+
+pub fn serialize_message(message: ParsedMessage) -> Result<Vec<u8>, SerializeError> {
+    message.to_bytes()
+}
+
+/// serialize_v1_packet_into writes the serialized packet into the given buffer without any allocation.
+/// It returns the number of the written bytes.
+pub fn serialize_v1_packet_into(
+    packet: &Packet<v1::Entry>,
+    buf: &mut [u8],
+) -> Result<usize, SerializeError> {
+    packet.write_bytes(buf)
+}
+
+/// serialize_v2_packet_into writes the serialized packet into the given buffer without any allocation.
+/// It returns the number of the written bytes.
+pub fn serialize_v2_packet_into(
+    packet: &Packet<v2::Entry>,
+    buf: &mut [u8],
+) -> Result<usize, SerializeError> {
+    packet.write_bytes(buf)
+}
+
+pub fn write_v1_packet<W: io::Write>(
+    packet: &Packet<v1::Entry>,
+    writer: &mut W,
+) -> io::Result<usize> {
+    write_packet(packet, writer)
+}
+
+pub fn write_v2_packet<W: io::Write>(
+    packet: &Packet<v2::Entry>,
+    writer: &mut W,
+) -> io::Result<usize> {
+    write_packet(packet, writer)
+}
+
+#[cfg(feature = "bytes")]
+pub fn put_message<B: bytes::BufMut>(
+    message: &ParsedMessage,
+    buf: &mut B,
+) -> Result<usize, SerializeError> {
+    put_packet(message, buf)
+}
+
+#[cfg(feature = "bytes")]
+pub fn put_v1_packet<B: bytes::BufMut>(
+    packet: &Packet<v1::Entry>,
+    buf: &mut B,
+) -> Result<usize, SerializeError> {
+    put_packet(packet, buf)
+}
+
+#[cfg(feature = "bytes")]
+pub fn put_v2_packet<B: bytes::BufMut>(
+    packet: &Packet<v2::Entry>,
+    buf: &mut B,
+) -> Result<usize, SerializeError> {
+    put_packet(packet, buf)
+}
+
+/// with_serialized serializes the packet into a buffer on the stack and passes the serialized bytes to the given function,
+/// so that writing out a packet doesn't allocate.
+fn with_serialized<T: Serializable, R>(
+    packet: &T,
+    f: impl FnOnce(&[u8]) -> R,
+) -> Result<R, SerializeError> {
+    let mut buf = [0; MAX_DATAGRAM_LENGTH];
+    let len = packet.write_bytes(&mut buf)?;
+    Ok(f(&buf[..len]))
+}
+
+fn write_packet<T: Serializable, W: io::Write>(packet: &T, writer: &mut W) -> io::Result<usize> {
+    with_serialized(packet, |bytes| writer.write_all(bytes).map(|_| bytes.len()))
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?
+}
+
+#[cfg(feature = "bytes")]
+fn put_packet<T: Serializable, B: bytes::BufMut>(
+    packet: &T,
+    buf: &mut B,
+) -> Result<usize, SerializeError> {
+    let len = packet.serialized_len();
+    if buf.remaining_mut() < len {
+        return Err(SerializeError::InsufficientBufferLength(
+            len,
+            buf.remaining_mut(),
+        ));
+    }
+
+    with_serialized(packet, |bytes| {
+        buf.put_slice(bytes);
+        bytes.len()
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use crate::header::Header;
+    use crate::metric::Metric;
     use crate::packet::Packet;
-    use crate::serializer::{serialize_v1_packet, serialize_v2_packet};
+    use crate::route_tag::RouteTag;
+    use crate::serializer::{
+        serialize_v1_packet, serialize_v1_packet_into, serialize_v2_packet,
+        serialize_v2_packet_into, write_v1_packet, write_v2_packet, SerializeError,
+    };
     use crate::{address_family, command, v1, v2, version};
+    use std::alloc::{GlobalAlloc, Layout, System};
+    use std::cell::Cell;
     use std::net::Ipv4Addr;
 
+    thread_local! {
+        static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+    }
+
+    /// CountingAllocator counts the allocations per thread, so that a test can assert that a code path doesn't allocate.
+    struct CountingAllocator;
+
+    unsafe impl GlobalAlloc for CountingAllocator {
+        unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+            let _ = ALLOCATIONS.try_with(|allocations| allocations.set(allocations.get() + 1));
+            System.alloc(layout)
+        }
+
+        unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+            System.dealloc(ptr, layout)
+        }
+    }
+
+    #[global_allocator]
+    static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+    fn count_allocations<R>(f: impl FnOnce() -> R) -> (R, usize) {
+        let before = ALLOCATIONS.with(|allocations| allocations.get());
+        let result = f();
+        (
+            result,
+            ALLOCATIONS.with(|allocations| allocations.get()) - before,
+        )
+    }
+
+    fn make_v2_packet() -> Packet<v2::Entry> {
+        Packet::make_v2_packet(
+            Header::new(command::Kind::Response, version::Version::Version2),
+            vec![v2::Entry::new(
+                address_family::Identifier::IP,
+                RouteTag::new(258),
+                Ipv4Addr::new(192, 0, 2, 100),
+                Ipv4Addr::new(255, 255, 255, 0),
+                Ipv4Addr::new(192, 0, 2, 111),
+                Metric::from_raw(67305985),
+            )],
+        )
+        .unwrap()
+    }
+
     #[test]
     fn test_v1_packet_has_single_entry_to_bytes() {
         let packet = Packet::make_v1_packet(
@@ -40,14 +224,14 @@ mod tests {
             vec![v1::Entry::new(
                 address_family::Identifier::IP,
                 Ipv4Addr::new(192, 0, 2, 100),
-                67305985,
+                Metric::from_raw(67305985),
             )],
         )
         .unwrap();
 
         let serialization_result = serialize_v1_packet(packet);
 
-        assert_eq!(serialization_result.is_ok(), true);
+        assert!(serialization_result.is_ok());
         assert_eq!(
             serialization_result.unwrap(),
             vec![
@@ -69,17 +253,17 @@ mod tests {
                 v1::Entry::new(
                     address_family::Identifier::IP,
                     Ipv4Addr::new(192, 0, 2, 100),
-                    67305985,
+                    Metric::from_raw(67305985),
                 ),
                 v1::Entry::new(
                     address_family::Identifier::IP,
                     Ipv4Addr::new(192, 0, 2, 101),
-                    1,
+                    Metric::from_raw(1),
                 ),
                 v1::Entry::new(
                     address_family::Identifier::IP,
                     Ipv4Addr::new(192, 0, 2, 102),
-                    2,
+                    Metric::from_raw(2),
                 ),
             ],
         )
@@ -87,7 +271,7 @@ mod tests {
 
         let serialization_result = serialize_v1_packet(packet);
 
-        assert_eq!(serialization_result.is_ok(), true);
+        assert!(serialization_result.is_ok());
         assert_eq!(
             serialization_result.unwrap(),
             vec![
@@ -117,18 +301,18 @@ mod tests {
             Header::new(command::Kind::Response, version::Version::Version2),
             vec![v2::Entry::new(
                 address_family::Identifier::IP,
-                258,
+                RouteTag::new(258),
                 Ipv4Addr::new(192, 0, 2, 100),
                 Ipv4Addr::new(255, 255, 255, 0),
                 Ipv4Addr::new(192, 0, 2, 111),
-                67305985,
+                Metric::from_raw(67305985),
             )],
         )
         .unwrap();
 
         let serialization_result = serialize_v2_packet(packet);
 
-        assert_eq!(serialization_result.is_ok(), true);
+        assert!(serialization_result.is_ok());
         assert_eq!(
             serialization_result.unwrap(),
             vec![
@@ -149,27 +333,27 @@ mod tests {
             vec![
                 v2::Entry::new(
                     address_family::Identifier::IP,
-                    258,
+                    RouteTag::new(258),
                     Ipv4Addr::new(192, 0, 2, 100),
                     Ipv4Addr::new(255, 255, 255, 0),
                     Ipv4Addr::new(192, 0, 2, 200),
-                    67305985,
+                    Metric::from_raw(67305985),
                 ),
                 v2::Entry::new(
                     address_family::Identifier::IP,
-                    1,
+                    RouteTag::new(1),
                     Ipv4Addr::new(192, 0, 2, 101),
                     Ipv4Addr::new(255, 255, 255, 0),
                     Ipv4Addr::new(192, 0, 2, 201),
-                    1,
+                    Metric::from_raw(1),
                 ),
                 v2::Entry::new(
                     address_family::Identifier::IP,
-                    2,
+                    RouteTag::new(2),
                     Ipv4Addr::new(192, 0, 2, 102),
                     Ipv4Addr::new(255, 255, 255, 0),
                     Ipv4Addr::new(192, 0, 2, 202),
-                    2,
+                    Metric::from_raw(2),
                 ),
             ],
         )
@@ -177,7 +361,7 @@ mod tests {
 
         let serialization_result = serialize_v2_packet(packet);
 
-        assert_eq!(serialization_result.is_ok(), true);
+        assert!(serialization_result.is_ok());
         assert_eq!(
             serialization_result.unwrap(),
             vec![
@@ -200,4 +384,123 @@ mod tests {
             ]
         );
     }
+
+    #[test]
+    fn test_serialize_v1_packet_into_buffer() {
+        let packet = Packet::make_v1_packet(
+            Header::new(command::Kind::Response, version::Version::Version1),
+            vec![v1::Entry::new(
+                address_family::Identifier::IP,
+                Ipv4Addr::new(192, 0, 2, 100),
+                Metric::from_raw(67305985),
+            )],
+        )
+        .unwrap();
+        assert_eq!(packet.serialized_len(), 24);
+
+        let mut buf = [0xff; 32];
+        let len = serialize_v1_packet_into(&packet, &mut buf).unwrap();
+        assert_eq!(len, 24);
+        assert_eq!(
+            &buf[..len],
+            &[
+                2, 1, 0, 0, //
+                0, 2, 0, 0, //
+                192, 0, 2, 100, //
+                0, 0, 0, 0, //
+                0, 0, 0, 0, //
+                4, 3, 2, 1, //
+            ]
+        );
+        assert_eq!(&buf[len..], &[0xff; 8]);
+    }
+
+    #[test]
+    fn test_serialize_v2_packet_into_buffer() {
+        let packet = make_v2_packet();
+        assert_eq!(packet.serialized_len(), 24);
+
+        let mut buf = [0; 24];
+        assert_eq!(serialize_v2_packet_into(&packet, &mut buf).unwrap(), 24);
+        assert_eq!(buf.to_vec(), serialize_v2_packet(packet).unwrap());
+    }
+
+    #[test]
+    fn test_serialize_packet_into_too_small_buffer() {
+        let packet = make_v2_packet();
+
+        let mut buf = [0; 23];
+        assert_eq!(
+            serialize_v2_packet_into(&packet, &mut buf).unwrap_err(),
+            SerializeError::InsufficientBufferLength(24, 23)
+        );
+    }
+
+    #[test]
+    fn test_write_packet() {
+        let packet = Packet::make_v1_packet(
+            Header::new(command::Kind::Request, version::Version::Version1),
+            vec![v1::Entry::new(
+                address_family::Identifier::Unspecified,
+                Ipv4Addr::new(0, 0, 0, 0),
+                Metric::from_raw(16),
+            )],
+        )
+        .unwrap();
+
+        let mut written = vec![];
+        assert_eq!(write_v1_packet(&packet, &mut written).unwrap(), 24);
+        assert_eq!(written, serialize_v1_packet(packet).unwrap());
+
+        let packet = make_v2_packet();
+        let mut written = vec![];
+        assert_eq!(write_v2_packet(&packet, &mut written).unwrap(), 24);
+        assert_eq!(written, serialize_v2_packet(packet).unwrap());
+    }
+
+    #[test]
+    fn test_write_packet_without_allocation() {
+        let packet = make_v2_packet();
+        let mut storage = [0; 32];
+
+        let (result, allocations) =
+            count_allocations(|| write_v2_packet(&packet, &mut &mut storage[..]));
+        assert_eq!(result.unwrap(), 24);
+        assert_eq!(allocations, 0);
+        assert_eq!(
+            storage[..24].to_vec(),
+            serialize_v2_packet(make_v2_packet()).unwrap()
+        );
+    }
+
+    #[cfg(feature = "bytes")]
+    #[test]
+    fn test_put_packet() {
+        use crate::serializer::put_v2_packet;
+        use bytes::BufMut;
+
+        let packet = make_v2_packet();
+
+        let mut buf = bytes::BytesMut::new();
+        assert_eq!(put_v2_packet(&packet, &mut buf).unwrap(), 24);
+        assert_eq!(buf.to_vec(), serialize_v2_packet(make_v2_packet()).unwrap());
+
+        let mut storage = [0; 32];
+        let (result, allocations) =
+            count_allocations(|| put_v2_packet(&packet, &mut &mut storage[..]));
+        assert_eq!(result.unwrap(), 24);
+        assert_eq!(allocations, 0);
+        assert_eq!(
+            storage[..24].to_vec(),
+            serialize_v2_packet(make_v2_packet()).unwrap()
+        );
+
+        let mut storage = [0; 16];
+        let mut limited = &mut storage[..];
+        assert_eq!(limited.remaining_mut(), 16);
+        assert_eq!(
+            put_v2_packet(&packet, &mut limited).unwrap_err(),
+            SerializeError::InsufficientBufferLength(24, 16)
+        );
+    }
 }
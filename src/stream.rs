@@ -0,0 +1,468 @@
+use crate::packet::{ENTRY_LENGTH, HEADER_LENGTH, MAX_DATAGRAM_LENGTH, MAX_ENTRIES_NUM};
+use crate::parser::{self, ParseError, ParsedPacket};
+use crate::serializer::Serializable;
+use crate::{address_family, command, version};
+use std::io;
+use std::io::{Read, Write};
+use thiserror::Error;
+
+/// The number of bytes that a decoder reads from the underlying reader at once.
+const READ_CHUNK_LENGTH: usize = 4096;
+
+#[derive(Error, Debug)]
+pub enum StreamError {
+    #[error("I/O error: {0}")]
+    Io(#[from] io::Error),
+    #[error("corrupt record at {1} byte of the stream: {0}")]
+    CorruptRecord(ParseError, u64),
+    #[error("unrecognizable {0} byte(s) at {1} byte of the stream")]
+    UnrecognizableBytes(usize, u64),
+    #[error("the stream ends in the middle of the record at {0} byte")]
+    TruncatedRecord(u64),
+    #[error("the record length {0} exceeds the maximum length of the framing")]
+    RecordTooLong(usize),
+}
+
+/// Framing is the way how RIP packets are laid out in a stream.
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub enum Framing {
+    /// LengthPrefixed precedes each packet with its length as a 2-byte big-endian integer.
+    LengthPrefixed,
+    /// Concatenated lays out the raw packets back to back. The boundary of a packet is inferred by the packet layout:
+    /// a packet continues while the next 20 bytes start with a known address family identifier,
+    /// up to MAX_ENTRIES_NUM entries.
+    Concatenated,
+}
+
+/// Record is a packet that is decoded from a stream with the offset where the record starts.
+#[derive(Debug)]
+pub struct Record {
+    offset: u64,
+    packet: ParsedPacket,
+}
+
+impl Record {
+    pub fn get_offset(&self) -> u64 {
+        self.offset
+    }
+
+    pub fn get_packet(&self) -> &ParsedPacket {
+        &self.packet
+    }
+
+    pub fn into_packet(self) -> ParsedPacket {
+        self.packet
+    }
+}
+
+/// Scan is the result of scanning the head of the buffered bytes.
+#[derive(PartialEq, Debug)]
+pub(crate) enum Scan {
+    /// NeedMore requires more bytes to find the end of the record.
+    NeedMore,
+    /// Record has found a record; the packet occupies `packet_range` of the `len` bytes of the record.
+    Record {
+        len: usize,
+        packet_range: std::ops::Range<usize>,
+    },
+    /// Skip has found the bytes that can't be a record.
+    Skip(usize),
+    /// Truncated has found the record that is cut by the end of the stream.
+    Truncated(usize),
+}
+
+/// scan finds the record at the head of the buffer; `eof` tells that no more bytes follow the buffer.
+pub(crate) fn scan(framing: Framing, buf: &[u8], eof: bool) -> Scan {
+    match framing {
+        Framing::LengthPrefixed => {
+            if buf.len() < 2 {
+                return scan_shortage(buf, eof);
+            }
+            let len = u16::from_be_bytes([buf[0], buf[1]]) as usize;
+            if buf.len() < 2 + len {
+                return scan_shortage(buf, eof);
+            }
+            Scan::Record {
+                len: 2 + len,
+                packet_range: 2..2 + len,
+            }
+        }
+        Framing::Concatenated => {
+            if buf.len() < HEADER_LENGTH {
+                return scan_shortage(buf, eof);
+            }
+            if !is_plausible_header(buf) {
+                let next = (1..buf.len()).find(|head| {
+                    buf.len() - head >= HEADER_LENGTH && is_plausible_header(&buf[*head..])
+                });
+                return match next {
+                    Some(head) => Scan::Skip(head),
+                    None if eof => Scan::Skip(buf.len()),
+                    None if buf.len() > MAX_DATAGRAM_LENGTH => {
+                        Scan::Skip(buf.len() - (HEADER_LENGTH - 1))
+                    }
+                    None => Scan::NeedMore,
+                };
+            }
+
+            let mut end = HEADER_LENGTH;
+            for _ in 0..MAX_ENTRIES_NUM {
+                if buf.len() < end + 2 {
+                    if eof {
+                        break;
+                    }
+                    return Scan::NeedMore;
+                }
+                let afi = address_family::Identifier::from_u16(u16::from_be_bytes([
+                    buf[end],
+                    buf[end + 1],
+                ]));
+                if let address_family::Identifier::Unknown(_) = afi {
+                    break;
+                }
+                if buf.len() < end + ENTRY_LENGTH {
+                    if eof {
+                        return Scan::Truncated(buf.len());
+                    }
+                    return Scan::NeedMore;
+                }
+                end += ENTRY_LENGTH;
+            }
+            Scan::Record {
+                len: end,
+                packet_range: 0..end,
+            }
+        }
+    }
+}
+
+fn scan_shortage(buf: &[u8], eof: bool) -> Scan {
+    if eof && !buf.is_empty() {
+        return Scan::Truncated(buf.len());
+    }
+    Scan::NeedMore
+}
+
+/// is_plausible_header returns whether the bytes start with a header that a RIPv1/RIPv2 packet can have.
+/// The invalid command is excluded since its header is indistinguishable from the head of an entry.
+fn is_plausible_header(bytes: &[u8]) -> bool {
+    let command = command::Kind::from_u8(bytes[0]);
+    let version = version::Version::from_u8(bytes[1]);
+    !matches!(command, command::Kind::Invalid | command::Kind::Unknown(_))
+        && matches!(
+            version,
+            version::Version::Version1 | version::Version::Version2
+        )
+        && bytes[2] == 0
+        && bytes[3] == 0
+}
+
+/// RecordBuffer is the buffer of the bytes read from a stream that the decoders share.
+#[derive(Default)]
+struct RecordBuffer {
+    bytes: Vec<u8>,
+    offset: u64,
+    eof: bool,
+}
+
+impl RecordBuffer {
+    /// next_record takes the next record from the buffer; it returns None when it needs more bytes.
+    fn next_record(&mut self, framing: Framing) -> Option<Option<Result<Record, StreamError>>> {
+        let scanned = scan(framing, &self.bytes, self.eof);
+        let offset = self.offset;
+        let (consumed, result) = match scanned {
+            Scan::NeedMore => {
+                if self.eof {
+                    return Some(None);
+                }
+                return None;
+            }
+            Scan::Record { len, packet_range } => (
+                len,
+                match parser::parse_packet(&self.bytes[packet_range]) {
+                    Ok(packet) => Ok(Record { offset, packet }),
+                    Err(e) => Err(StreamError::CorruptRecord(e, offset)),
+                },
+            ),
+            Scan::Skip(len) => (len, Err(StreamError::UnrecognizableBytes(len, offset))),
+            Scan::Truncated(len) => (len, Err(StreamError::TruncatedRecord(offset))),
+        };
+
+        self.bytes.drain(..consumed);
+        self.offset += consumed as u64;
+        Some(Some(result))
+    }
+}
+
+/// Decoder reads the RIP packets from the reader one by one.
+/// A corrupt record is reported as an error and the decoder continues from the next record.
+pub struct Decoder<R: Read> {
+    reader: R,
+    framing: Framing,
+    buffer: RecordBuffer,
+}
+
+impl<R: Read> Decoder<R> {
+    pub fn new(reader: R, framing: Framing) -> Self {
+        Decoder {
+            reader,
+            framing,
+            buffer: RecordBuffer::default(),
+        }
+    }
+
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+}
+
+impl<R: Read> Iterator for Decoder<R> {
+    type Item = Result<Record, StreamError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(record) = self.buffer.next_record(self.framing) {
+                return record;
+            }
+
+            let mut chunk = [0; READ_CHUNK_LENGTH];
+            match self.reader.read(&mut chunk) {
+                Ok(0) => self.buffer.eof = true,
+                Ok(n) => self.buffer.bytes.extend_from_slice(&chunk[..n]),
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => {
+                    self.buffer.eof = true;
+                    return Some(Err(StreamError::Io(e)));
+                }
+            }
+        }
+    }
+}
+
+/// AsyncDecoder is the asynchronous variant of [`Decoder`].
+#[cfg(feature = "tokio")]
+pub struct AsyncDecoder<R: tokio::io::AsyncRead + Unpin> {
+    reader: R,
+    framing: Framing,
+    buffer: RecordBuffer,
+}
+
+#[cfg(feature = "tokio")]
+impl<R: tokio::io::AsyncRead + Unpin> AsyncDecoder<R> {
+    pub fn new(reader: R, framing: Framing) -> Self {
+        AsyncDecoder {
+            reader,
+            framing,
+            buffer: RecordBuffer::default(),
+        }
+    }
+
+    /// next_record returns the next record, or None at the end of the stream.
+    pub async fn next_record(&mut self) -> Option<Result<Record, StreamError>> {
+        use tokio::io::AsyncReadExt;
+
+        loop {
+            if let Some(record) = self.buffer.next_record(self.framing) {
+                return record;
+            }
+
+            let mut chunk = [0; READ_CHUNK_LENGTH];
+            match self.reader.read(&mut chunk).await {
+                Ok(0) => self.buffer.eof = true,
+                Ok(n) => self.buffer.bytes.extend_from_slice(&chunk[..n]),
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => {
+                    self.buffer.eof = true;
+                    return Some(Err(StreamError::Io(e)));
+                }
+            }
+        }
+    }
+
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+}
+
+/// Encoder writes the RIP packets into the writer in the framing that [`Decoder`] reads.
+pub struct Encoder<W: Write> {
+    writer: W,
+    framing: Framing,
+}
+
+impl<W: Write> Encoder<W> {
+    pub fn new(writer: W, framing: Framing) -> Self {
+        Encoder { writer, framing }
+    }
+
+    /// write_packet writes the packet and returns the number of the written bytes including the framing.
+    pub fn write_packet<T: Serializable>(&mut self, packet: &T) -> Result<usize, StreamError> {
+        let bytes = packet
+            .to_bytes()
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+
+        let mut written = 0;
+        if self.framing == Framing::LengthPrefixed {
+            let len =
+                u16::try_from(bytes.len()).map_err(|_| StreamError::RecordTooLong(bytes.len()))?;
+            self.writer.write_all(&len.to_be_bytes())?;
+            written += 2;
+        }
+        self.writer.write_all(&bytes)?;
+
+        Ok(written + bytes.len())
+    }
+
+    pub fn flush(&mut self) -> Result<(), StreamError> {
+        Ok(self.writer.flush()?)
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::header::Header;
+    use crate::metric::Metric;
+    use crate::packet::Packet;
+    use crate::parser::{ParseError, ParsedPacket};
+    use crate::route_tag::RouteTag;
+    use crate::stream::{Decoder, Encoder, Framing, StreamError};
+    use crate::{address_family, command, v1, v2, version};
+    use std::net::Ipv4Addr;
+
+    fn v2_packet(num_of_entries: u8) -> Packet<v2::Entry> {
+        Packet::make_v2_packet(
+            Header::new(command::Kind::Response, version::Version::Version2),
+            (0..num_of_entries)
+                .map(|i| {
+                    v2::Entry::new(
+                        address_family::Identifier::IP,
+                        RouteTag::default(),
+                        Ipv4Addr::new(192, 0, 2, i),
+                        Ipv4Addr::new(255, 255, 255, 255),
+                        Ipv4Addr::UNSPECIFIED,
+                        Metric::from_raw(1),
+                    )
+                })
+                .collect(),
+        )
+        .unwrap()
+    }
+
+    fn v1_packet() -> Packet<v1::Entry> {
+        Packet::make_v1_packet(
+            Header::new(command::Kind::Response, version::Version::Version1),
+            vec![v1::Entry::new(
+                address_family::Identifier::IP,
+                Ipv4Addr::new(192, 0, 2, 100),
+                Metric::from_raw(3),
+            )],
+        )
+        .unwrap()
+    }
+
+    fn num_of_entries(packet: &ParsedPacket) -> usize {
+        packet.entries().count()
+    }
+
+    #[test]
+    fn test_round_trip() {
+        for framing in [Framing::LengthPrefixed, Framing::Concatenated] {
+            let mut encoder = Encoder::new(vec![], framing);
+            encoder.write_packet(&v2_packet(2)).unwrap();
+            encoder.write_packet(&v1_packet()).unwrap();
+            encoder.write_packet(&v2_packet(25)).unwrap();
+            encoder.write_packet(&v2_packet(0)).unwrap();
+            let bytes = encoder.into_inner();
+
+            let records = Decoder::new(bytes.as_slice(), framing)
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap();
+            assert_eq!(records.len(), 4);
+            assert_eq!(num_of_entries(records[0].get_packet()), 2);
+            assert!(matches!(records[1].get_packet(), ParsedPacket::V1(_)));
+            assert_eq!(num_of_entries(records[2].get_packet()), 25);
+            assert_eq!(num_of_entries(records[3].get_packet()), 0);
+
+            let prefix_len = match framing {
+                Framing::LengthPrefixed => 2,
+                Framing::Concatenated => 0,
+            };
+            assert_eq!(records[0].get_offset(), 0);
+            assert_eq!(records[1].get_offset(), (prefix_len + 44) as u64);
+            assert_eq!(records[2].get_offset(), (prefix_len * 2 + 44 + 24) as u64);
+        }
+    }
+
+    #[test]
+    fn test_recover_from_corrupt_length_prefixed_record() {
+        let mut encoder = Encoder::new(vec![], Framing::LengthPrefixed);
+        encoder.write_packet(&v2_packet(1)).unwrap();
+        let mut bytes = encoder.into_inner();
+        bytes.extend([0, 5, 2, 2, 0, 0, 0]); // a record that has a trailing byte
+        let mut encoder = Encoder::new(bytes, Framing::LengthPrefixed);
+        encoder.write_packet(&v2_packet(3)).unwrap();
+        let mut bytes = encoder.into_inner();
+        bytes.extend([0, 24, 2, 2]); // truncated by the end of the stream
+
+        let records = Decoder::new(bytes.as_slice(), Framing::LengthPrefixed).collect::<Vec<_>>();
+        assert_eq!(records.len(), 4);
+        assert!(records[0].is_ok());
+        assert!(matches!(
+            records[1],
+            Err(StreamError::CorruptRecord(
+                ParseError::TrailingBytes(1, 4),
+                26
+            ))
+        ));
+        assert_eq!(num_of_entries(records[2].as_ref().unwrap().get_packet()), 3);
+        assert!(matches!(records[3], Err(StreamError::TruncatedRecord(99))));
+    }
+
+    #[test]
+    fn test_recover_from_garbage_in_concatenated_stream() {
+        let mut encoder = Encoder::new(vec![0xde, 0xad, 0xbe, 0xef, 0xff], Framing::Concatenated);
+        encoder.write_packet(&v2_packet(2)).unwrap();
+        let mut bytes = encoder.into_inner();
+        bytes.extend([
+            2, 2, 0, 0, 0, 2, 0, 0, 192, 0, 2, 1, 255, 255, 255, 0, 0, 0, 0, 0,
+        ]); // truncated entry
+        bytes.extend([2, 2, 0]);
+
+        let records = Decoder::new(bytes.as_slice(), Framing::Concatenated).collect::<Vec<_>>();
+        assert_eq!(records.len(), 3);
+        assert!(matches!(
+            records[0],
+            Err(StreamError::UnrecognizableBytes(5, 0))
+        ));
+        assert_eq!(records[1].as_ref().unwrap().get_offset(), 5);
+        assert_eq!(num_of_entries(records[1].as_ref().unwrap().get_packet()), 2);
+        assert!(matches!(records[2], Err(StreamError::TruncatedRecord(49))));
+    }
+
+    #[cfg(feature = "tokio")]
+    #[test]
+    fn test_async_decoder() {
+        use crate::stream::AsyncDecoder;
+
+        let mut encoder = Encoder::new(vec![], Framing::LengthPrefixed);
+        encoder.write_packet(&v2_packet(2)).unwrap();
+        encoder.write_packet(&v1_packet()).unwrap();
+        let bytes = encoder.into_inner();
+
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        runtime.block_on(async {
+            let mut decoder = AsyncDecoder::new(bytes.as_slice(), Framing::LengthPrefixed);
+            let record = decoder.next_record().await.unwrap().unwrap();
+            assert_eq!(num_of_entries(record.get_packet()), 2);
+            let record = decoder.next_record().await.unwrap().unwrap();
+            assert_eq!(record.get_offset(), 46);
+            assert!(decoder.next_record().await.is_none());
+        });
+    }
+}
@@ -0,0 +1,543 @@
+use crate::header::Header;
+use crate::metric::Metric;
+use crate::packet::{Packet, PacketError};
+use crate::parser::ParsedPacket;
+use crate::prefix::{Ipv4Prefix, PrefixError};
+use crate::route_tag::{RouteTag, RouteTagError};
+use crate::{address_family, command, v1, v2, version};
+use std::net::Ipv4Addr;
+use std::str::FromStr;
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq, Clone)]
+pub enum TextError {
+    #[error("unexpected end of the text")]
+    UnexpectedEnd,
+    #[error("unexpected token \"{0}\"; at {1} byte")]
+    UnexpectedToken(String, usize),
+    #[error("unknown command \"{0}\"; at {1} byte")]
+    UnknownCommand(String, usize),
+    #[error("version \"{0}\" is not supported; at {1} byte")]
+    UnsupportedVersion(String, usize),
+    #[error("invalid IPv4 address \"{0}\"; at {1} byte")]
+    InvalidAddress(String, usize),
+    #[error("invalid prefix: {0}; at {1} byte")]
+    InvalidPrefix(PrefixError, usize),
+    #[error("invalid number \"{0}\"; at {1} byte")]
+    InvalidNumber(String, usize),
+    #[error("invalid route tag: {0}; at {1} byte")]
+    InvalidRouteTag(RouteTagError, usize),
+    #[error("the entry has neither a prefix length nor a subnet mask; at {0} byte")]
+    MissingSubnetMask(usize),
+    #[error("attribute \"{0}\" is given more than once; at {1} byte")]
+    DuplicatedAttribute(String, usize),
+    #[error("invalid packet: {0}; at {1} byte")]
+    InvalidPacket(PacketError, usize),
+}
+
+/// Token is a word of the text and its byte offset.
+type Token<'a> = (&'a str, usize);
+
+/// Tokens is the cursor over the words of the text.
+/// `{`, `}` and `;` are words by themselves, and `#` comments out the rest of the line.
+struct Tokens<'a> {
+    tokens: Vec<Token<'a>>,
+    position: usize,
+}
+
+impl<'a> Tokens<'a> {
+    fn new(text: &'a str) -> Self {
+        let mut tokens = Vec::new();
+        let mut start: Option<usize> = None;
+        let mut in_comment = false;
+
+        for (offset, c) in text.char_indices() {
+            if in_comment {
+                in_comment = c != '\n';
+                continue;
+            }
+
+            let is_delimiter = matches!(c, '{' | '}' | ';' | '#') || c.is_whitespace();
+            if is_delimiter {
+                if let Some(head) = start.take() {
+                    tokens.push((&text[head..offset], head));
+                }
+                match c {
+                    '#' => in_comment = true,
+                    '{' | '}' | ';' => tokens.push((&text[offset..offset + 1], offset)),
+                    _ => {}
+                }
+            } else if start.is_none() {
+                start = Some(offset);
+            }
+        }
+        if let Some(head) = start {
+            tokens.push((&text[head..], head));
+        }
+
+        Tokens {
+            tokens,
+            position: 0,
+        }
+    }
+
+    fn peek(&self) -> Option<Token<'a>> {
+        self.tokens.get(self.position).copied()
+    }
+
+    fn next(&mut self) -> Result<Token<'a>, TextError> {
+        let token = self.peek().ok_or(TextError::UnexpectedEnd)?;
+        self.position += 1;
+        Ok(token)
+    }
+
+    fn expect(&mut self, word: &str) -> Result<usize, TextError> {
+        let (token, offset) = self.next()?;
+        if token != word {
+            return Err(TextError::UnexpectedToken(token.to_string(), offset));
+        }
+        Ok(offset)
+    }
+
+    /// skip consumes the next token if it is the word.
+    fn skip(&mut self, word: &str) -> bool {
+        match self.peek() {
+            Some((token, _)) if token == word => {
+                self.position += 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn expect_end(&self) -> Result<(), TextError> {
+        match self.peek() {
+            Some((token, offset)) => Err(TextError::UnexpectedToken(token.to_string(), offset)),
+            None => Ok(()),
+        }
+    }
+}
+
+/// parse parses the text format of a single packet, e.g. `response v2 { 10.0.0.0/8 via 0.0.0.0 metric 2 tag 100; }`.
+/// It gives back the identical packet for the output of `Display` of [`Packet`] and [`ParsedPacket`].
+pub fn parse(text: &str) -> Result<ParsedPacket, TextError> {
+    let mut tokens = Tokens::new(text);
+    let packet = parse_packet(&mut tokens)?;
+    tokens.expect_end()?;
+    Ok(packet)
+}
+
+/// parse_all parses the text that has any number of packets one after another.
+pub fn parse_all(text: &str) -> Result<Vec<ParsedPacket>, TextError> {
+    let mut tokens = Tokens::new(text);
+    let mut packets = vec![];
+    while tokens.peek().is_some() {
+        packets.push(parse_packet(&mut tokens)?);
+    }
+    Ok(packets)
+}
+
+fn parse_packet(tokens: &mut Tokens) -> Result<ParsedPacket, TextError> {
+    let (header, offset) = parse_header(tokens)?;
+    tokens.expect("{")?;
+
+    match header.get_version() {
+        version::Version::Version1 => {
+            let entries = parse_entries(tokens, parse_v1_entry)?;
+            Packet::make_v1_packet(header, entries)
+                .map(ParsedPacket::V1)
+                .map_err(|e| TextError::InvalidPacket(e, offset))
+        }
+        version::Version::Version2 => {
+            let entries = parse_entries(tokens, parse_v2_entry)?;
+            Packet::make_v2_packet(header, entries)
+                .map(ParsedPacket::V2)
+                .map_err(|e| TextError::InvalidPacket(e, offset))
+        }
+        _ => Err(TextError::UnsupportedVersion(
+            header.get_version().to_string(),
+            offset,
+        )),
+    }
+}
+
+/// parse_header parses the command and the version, and returns the header with the offset of the packet.
+fn parse_header(tokens: &mut Tokens) -> Result<(Header, usize), TextError> {
+    let (command_token, offset) = tokens.next()?;
+    let command = command::Kind::from_str(command_token)
+        .map_err(|_| TextError::UnknownCommand(command_token.to_string(), offset))?;
+
+    let (version_token, version_offset) = tokens.next()?;
+    let version = version::Version::from_str(version_token)
+        .map_err(|_| TextError::UnsupportedVersion(version_token.to_string(), version_offset))?;
+
+    Ok((Header::new(command, version), offset))
+}
+
+/// parse_entries parses the entries that are terminated by `;` up to the closing `}`.
+/// The `;` after the last entry may be omitted.
+fn parse_entries<T>(
+    tokens: &mut Tokens,
+    parse_entry: fn(&mut Tokens) -> Result<T, TextError>,
+) -> Result<Vec<T>, TextError> {
+    let mut entries = vec![];
+    while !tokens.skip("}") {
+        entries.push(parse_entry(tokens)?);
+        if !tokens.skip(";") {
+            tokens.expect("}")?;
+            break;
+        }
+    }
+    Ok(entries)
+}
+
+fn parse_v1_entry(tokens: &mut Tokens) -> Result<v1::Entry, TextError> {
+    let ip_address = parse_address(tokens.next()?)?;
+
+    let mut metric = None;
+    let mut afi = None;
+    while let Some((attribute, offset)) = tokens.peek() {
+        match attribute {
+            "metric" => set_attribute(&mut metric, parse_metric(tokens)?, attribute, offset)?,
+            "afi" => set_attribute(&mut afi, parse_afi(tokens)?, attribute, offset)?,
+            ";" | "}" => break,
+            _ => return Err(TextError::UnexpectedToken(attribute.to_string(), offset)),
+        }
+    }
+
+    Ok(v1::Entry::new(
+        afi.unwrap_or(address_family::Identifier::IP),
+        ip_address,
+        metric.unwrap_or(Metric::from_raw(1)),
+    ))
+}
+
+fn parse_v2_entry(tokens: &mut Tokens) -> Result<v2::Entry, TextError> {
+    let (destination, destination_offset) = tokens.next()?;
+    let (ip_address, mut subnet_mask) = if destination.contains('/') {
+        let prefix = Ipv4Prefix::from_str(destination)
+            .map_err(|e| TextError::InvalidPrefix(e, destination_offset))?;
+        (prefix.get_address(), Some(prefix.get_subnet_mask()))
+    } else {
+        (parse_address((destination, destination_offset))?, None)
+    };
+    let has_prefix_length = subnet_mask.is_some();
+
+    let mut next_hop = None;
+    let mut metric = None;
+    let mut route_tag = None;
+    let mut afi = None;
+    while let Some((attribute, offset)) = tokens.peek() {
+        match attribute {
+            "mask" if !has_prefix_length => {
+                tokens.next()?;
+                let mask = parse_address(tokens.next()?)?;
+                set_attribute(&mut subnet_mask, mask, attribute, offset)?;
+            }
+            "via" => {
+                tokens.next()?;
+                let address = parse_address(tokens.next()?)?;
+                set_attribute(&mut next_hop, address, attribute, offset)?;
+            }
+            "metric" => set_attribute(&mut metric, parse_metric(tokens)?, attribute, offset)?,
+            "tag" => {
+                tokens.next()?;
+                let (tag, tag_offset) = tokens.next()?;
+                let tag = RouteTag::from_str(tag)
+                    .map_err(|e| TextError::InvalidRouteTag(e, tag_offset))?;
+                set_attribute(&mut route_tag, tag, attribute, offset)?;
+            }
+            "afi" => set_attribute(&mut afi, parse_afi(tokens)?, attribute, offset)?,
+            ";" | "}" => break,
+            _ => return Err(TextError::UnexpectedToken(attribute.to_string(), offset)),
+        }
+    }
+
+    Ok(v2::Entry::new(
+        afi.unwrap_or(address_family::Identifier::IP),
+        route_tag.unwrap_or_default(),
+        ip_address,
+        subnet_mask.ok_or(TextError::MissingSubnetMask(destination_offset))?,
+        next_hop.unwrap_or(Ipv4Addr::UNSPECIFIED),
+        metric.unwrap_or(Metric::from_raw(1)),
+    ))
+}
+
+fn set_attribute<T>(
+    slot: &mut Option<T>,
+    value: T,
+    attribute: &str,
+    offset: usize,
+) -> Result<(), TextError> {
+    if slot.replace(value).is_some() {
+        return Err(TextError::DuplicatedAttribute(
+            attribute.to_string(),
+            offset,
+        ));
+    }
+    Ok(())
+}
+
+fn parse_address((token, offset): Token) -> Result<Ipv4Addr, TextError> {
+    Ipv4Addr::from_str(token).map_err(|_| TextError::InvalidAddress(token.to_string(), offset))
+}
+
+/// parse_metric parses `metric <n>` leniently; an out-of-range metric is kept as-is like the packet parser does.
+fn parse_metric(tokens: &mut Tokens) -> Result<Metric, TextError> {
+    tokens.next()?;
+    let (token, offset) = tokens.next()?;
+    u32::from_str(token)
+        .map(Metric::from_raw)
+        .map_err(|_| TextError::InvalidNumber(token.to_string(), offset))
+}
+
+fn parse_afi(tokens: &mut Tokens) -> Result<address_family::Identifier, TextError> {
+    tokens.next()?;
+    let (token, offset) = tokens.next()?;
+    u16::from_str(token)
+        .map(address_family::Identifier::from_u16)
+        .map_err(|_| TextError::InvalidNumber(token.to_string(), offset))
+}
+
+impl FromStr for ParsedPacket {
+    type Err = TextError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse(s)
+    }
+}
+
+impl FromStr for Packet<v1::Entry> {
+    type Err = TextError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match parse(s)? {
+            ParsedPacket::V1(packet) => Ok(packet),
+            ParsedPacket::V2(_) => Err(TextError::InvalidPacket(
+                PacketError::VersionInHeaderConflicted,
+                s.len() - s.trim_start().len(),
+            )),
+        }
+    }
+}
+
+impl FromStr for Packet<v2::Entry> {
+    type Err = TextError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match parse(s)? {
+            ParsedPacket::V2(packet) => Ok(packet),
+            ParsedPacket::V1(_) => Err(TextError::InvalidPacket(
+                PacketError::VersionInHeaderConflicted,
+                s.len() - s.trim_start().len(),
+            )),
+        }
+    }
+}
+
+impl FromStr for v1::Entry {
+    type Err = TextError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut tokens = Tokens::new(s);
+        let entry = parse_v1_entry(&mut tokens)?;
+        tokens.expect_end()?;
+        Ok(entry)
+    }
+}
+
+impl FromStr for v2::Entry {
+    type Err = TextError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut tokens = Tokens::new(s);
+        let entry = parse_v2_entry(&mut tokens)?;
+        tokens.expect_end()?;
+        Ok(entry)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::address_family::Identifier;
+    use crate::header::Header;
+    use crate::metric::Metric;
+    use crate::packet::{Packet, PacketError};
+    use crate::parser::{self, ParsedPacket};
+    use crate::prefix::PrefixError;
+    use crate::route_tag::RouteTag;
+    use crate::text::{parse, parse_all, TextError};
+    use crate::{command, v1, v2, version};
+    use std::net::Ipv4Addr;
+
+    #[test]
+    fn test_display_and_parse_v2() {
+        let packet = Packet::make_v2_packet(
+            Header::new(command::Kind::Response, version::Version::Version2),
+            vec![
+                v2::Entry::new(
+                    Identifier::IP,
+                    RouteTag::new(100),
+                    Ipv4Addr::new(10, 0, 0, 0),
+                    Ipv4Addr::new(255, 0, 0, 0),
+                    Ipv4Addr::new(0, 0, 0, 0),
+                    Metric::from_raw(2),
+                ),
+                v2::Entry::new(
+                    Identifier::Unspecified,
+                    RouteTag::new(0),
+                    Ipv4Addr::new(192, 0, 2, 1),
+                    Ipv4Addr::new(255, 0, 255, 0),
+                    Ipv4Addr::new(192, 0, 2, 111),
+                    Metric::from_raw(67305985),
+                ),
+            ],
+        )
+        .unwrap();
+
+        let text = packet.to_string();
+        assert_eq!(
+            text,
+            "response v2 { 10.0.0.0/8 via 0.0.0.0 metric 2 tag 100; 192.0.2.1 mask 255.0.255.0 via 192.0.2.111 metric 67305985 tag 0 afi 0; }"
+        );
+        assert_eq!(text.parse::<Packet<v2::Entry>>().unwrap(), packet);
+
+        let pretty = format!("{:#}", ParsedPacket::V2(packet));
+        assert_eq!(
+            pretty,
+            [
+                "response v2 {\n",
+                "    10.0.0.0/8 via 0.0.0.0 metric 2 tag 100;\n",
+                "    192.0.2.1 mask 255.0.255.0 via 192.0.2.111 metric 67305985 tag 0 afi 0;\n",
+                "}",
+            ]
+            .concat()
+        );
+        assert_eq!(parse(&pretty).unwrap().to_string(), text);
+    }
+
+    #[test]
+    fn test_display_and_parse_v1() {
+        let packet = Packet::make_v1_packet(
+            Header::new(command::Kind::Unknown(42), version::Version::Version1),
+            vec![v1::Entry::new(
+                Identifier::IP,
+                Ipv4Addr::new(192, 0, 2, 100),
+                Metric::from_raw(1),
+            )],
+        )
+        .unwrap();
+
+        let text = packet.to_string();
+        assert_eq!(text, "unknown(42) v1 { 192.0.2.100 metric 1; }");
+        assert_eq!(text.parse::<Packet<v1::Entry>>().unwrap(), packet);
+
+        let empty = "update-ack v1 { }".parse::<Packet<v1::Entry>>().unwrap();
+        assert_eq!(
+            empty.get_header().get_command(),
+            command::Kind::UpdateAcknowledge
+        );
+        assert_eq!(empty.to_string(), "update-ack v1 { }");
+        assert_eq!(format!("{:#}", empty), "update-ack v1 { }");
+    }
+
+    #[test]
+    fn test_parse_defaults_and_comments() {
+        let packets = parse_all(
+            "# a full table request\n\
+             request v2 { 0.0.0.0/0 metric 16 afi 0 }\n\
+             response v2 { 10.0.0.0 mask 255.0.0.0; } # a route with the default attributes\n\
+             response v1 { 10.0.0.0; }",
+        )
+        .unwrap();
+        assert_eq!(packets.len(), 3);
+        assert_eq!(
+            packets[0].to_string(),
+            "request v2 { 0.0.0.0/0 via 0.0.0.0 metric 16 tag 0 afi 0; }"
+        );
+        assert_eq!(
+            packets[1].to_string(),
+            "response v2 { 10.0.0.0/8 via 0.0.0.0 metric 1 tag 0; }"
+        );
+        assert_eq!(packets[2].to_string(), "response v1 { 10.0.0.0 metric 1; }");
+
+        let entry: v2::Entry = "192.0.2.0/24 tag 0x1f metric 3".parse().unwrap();
+        assert_eq!(entry.get_route_tag(), RouteTag::new(0x1f));
+        assert_eq!(entry.get_metric(), Metric::from_raw(3));
+    }
+
+    #[test]
+    fn test_parse_round_trips_the_wire_format() {
+        let bytes = vec![
+            2, 2, 0, 0, //
+            0, 2, 1, 2, //
+            192, 0, 2, 100, //
+            255, 255, 255, 0, //
+            192, 0, 2, 111, //
+            0, 0, 0, 1, //
+        ];
+        let packet = parser::parse_packet(bytes.as_slice()).unwrap();
+        assert_eq!(parse(&packet.to_string()).unwrap(), packet);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            parse("respond v2 { }"),
+            Err(TextError::UnknownCommand("respond".to_string(), 0))
+        );
+        assert_eq!(
+            parse("response v3 { }"),
+            Err(TextError::UnsupportedVersion("v3".to_string(), 0))
+        );
+        assert_eq!(
+            parse("response 2 { }"),
+            Err(TextError::UnsupportedVersion("2".to_string(), 9))
+        );
+        assert_eq!(
+            parse("response v2 { 10.0.0.0/8"),
+            Err(TextError::UnexpectedEnd)
+        );
+        assert_eq!(
+            parse("response v2 { 10.0.0.1/8; }"),
+            Err(TextError::InvalidPrefix(
+                PrefixError::NonCanonicalPrefix(Ipv4Addr::new(10, 0, 0, 1), 8),
+                14
+            ))
+        );
+        assert_eq!(
+            parse("response v2 { 10.0.0.0; }"),
+            Err(TextError::MissingSubnetMask(14))
+        );
+        assert_eq!(
+            parse("response v2 { 10.0.0.0/8 metric 1 metric 2; }"),
+            Err(TextError::DuplicatedAttribute("metric".to_string(), 34))
+        );
+        assert_eq!(
+            parse("response v1 { 10.0.0.0 via 192.0.2.1; }"),
+            Err(TextError::UnexpectedToken("via".to_string(), 23))
+        );
+        assert_eq!(
+            parse("response v2 { 10.0.0.0/8 via 192.0.2; }"),
+            Err(TextError::InvalidAddress("192.0.2".to_string(), 29))
+        );
+        assert_eq!(
+            parse("response v2 { } response v2 { }"),
+            Err(TextError::UnexpectedToken("response".to_string(), 16))
+        );
+        assert_eq!(
+            parse("reserved v2 { }"),
+            Err(TextError::InvalidPacket(
+                PacketError::EmptyRIPEntry(command::Kind::Reserved),
+                0
+            ))
+        );
+        assert_eq!(
+            "response v1 { }".parse::<Packet<v2::Entry>>(),
+            Err(TextError::InvalidPacket(
+                PacketError::VersionInHeaderConflicted,
+                0
+            ))
+        );
+    }
+}
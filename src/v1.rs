@@ -1,9 +1,14 @@
+use crate::entry::RouteEntry;
 use crate::metric::Metric;
-use crate::serializer::{Serializable, SerializeError};
-use crate::{address_family, ipv4, metric, parser::PacketParsable, parser::ParseError, zero_bytes};
+use crate::parser::{PacketParsable, Parsable, ParseError, Parsed};
+use crate::route_tag::RouteTag;
+use crate::serializer::{check_buffer_length, Serializable, SerializeError};
+use crate::{address_family, ipv4, metric, zero_bytes};
+use std::fmt;
 use std::net::Ipv4Addr;
 
 #[derive(PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Entry {
     address_family_identifier: address_family::Identifier,
     ip_address: Ipv4Addr,
@@ -36,19 +41,96 @@ impl Entry {
     }
 }
 
+impl RouteEntry for Entry {
+    fn address_family_identifier(&self) -> address_family::Identifier {
+        self.get_address_family_identifier()
+    }
+
+    fn destination(&self) -> Ipv4Addr {
+        self.get_ip_address()
+    }
+
+    fn subnet_mask(&self) -> Option<Ipv4Addr> {
+        None
+    }
+
+    fn next_hop(&self) -> Option<Ipv4Addr> {
+        None
+    }
+
+    fn route_tag(&self) -> Option<RouteTag> {
+        None
+    }
+
+    fn metric(&self) -> Metric {
+        self.get_metric()
+    }
+}
+
+/// The entry is formatted as `192.0.2.0 metric 1`; the address family identifier follows only when it is not IP.
+impl fmt::Display for Entry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} metric {}", self.ip_address, self.metric)?;
+        if self.address_family_identifier != address_family::Identifier::IP {
+            write!(f, " afi {}", self.address_family_identifier.to_u16())?;
+        }
+        Ok(())
+    }
+}
+
 impl Serializable for Entry {
-    fn to_bytes(&self) -> Result<Vec<u8>, SerializeError> {
-        Ok([
-            self.get_address_family_identifier().to_bytes()?,
-            vec![0, 0],
-            ipv4::to_bytes(self.get_ip_address())?,
-            vec![0, 0, 0, 0, 0, 0, 0, 0],
-            metric::to_bytes(self.get_metric())?,
-        ]
-        .concat())
+    fn serialized_len(&self) -> usize {
+        20
+    }
+
+    fn write_bytes(&self, buf: &mut [u8]) -> Result<usize, SerializeError> {
+        check_buffer_length(buf, self.serialized_len())?;
+
+        let mut cursor = self.get_address_family_identifier().write_bytes(buf)?;
+        buf[cursor..cursor + 2].fill(0);
+        cursor += 2;
+        cursor += ipv4::write_bytes(self.get_ip_address(), &mut buf[cursor..])?;
+        buf[cursor..cursor + 8].fill(0);
+        cursor += 8;
+        cursor += metric::write_bytes(self.get_metric(), &mut buf[cursor..])?;
+
+        Ok(cursor)
+    }
+}
+
+impl Parsable for Entry {
+    fn parse(cursor: usize, bytes: &[u8]) -> Result<Parsed<Entry>, ParseError> {
+        parse_entry(cursor, bytes, false)
     }
 }
 
+/// parse_entry parses an entry; the unknown address family identifier is an error unless
+/// `allows_unknown_address_family` is true.
+fn parse_entry(
+    cursor: usize,
+    bytes: &[u8],
+    allows_unknown_address_family: bool,
+) -> Result<Parsed<Entry>, ParseError> {
+    let (address_family_identifier, cursor) = if allows_unknown_address_family {
+        address_family::parse_any(cursor, bytes)?
+    } else {
+        address_family::Identifier::parse(cursor, bytes)?
+    };
+
+    let cursor = zero_bytes::skip(2, cursor, bytes)?;
+
+    let (ip_address, cursor) = ipv4::parse(cursor, bytes)?;
+
+    let cursor = zero_bytes::skip(8, cursor, bytes)?;
+
+    let (metric, cursor) = metric::parse(cursor, bytes)?;
+
+    Ok((
+        Entry::new(address_family_identifier, ip_address, metric),
+        cursor,
+    ))
+}
+
 pub struct EntriesParser {}
 
 impl PacketParsable<Entry> for EntriesParser {
@@ -57,25 +139,27 @@ impl PacketParsable<Entry> for EntriesParser {
         cursor: usize,
         bytes: &'a [u8],
     ) -> Result<(Entry, usize), ParseError> {
-        let (address_family_identifier, cursor) = address_family::Identifier::parse(cursor, bytes)?;
-
-        let cursor = zero_bytes::skip(2, cursor, bytes)?;
-
-        let (ip_address, cursor) = ipv4::parse(cursor, bytes)?;
-
-        let cursor = zero_bytes::skip(8, cursor, bytes)?;
+        Entry::parse(cursor, bytes)
+    }
+}
 
-        let (metric, cursor) = metric::parse(cursor, bytes)?;
+/// AnyAddressFamilyEntriesParser parses the entries as well as [`EntriesParser`],
+/// but it accepts the unknown address family identifiers.
+pub(crate) struct AnyAddressFamilyEntriesParser {}
 
-        Ok((
-            Entry::new(address_family_identifier, ip_address, metric),
-            cursor,
-        ))
+impl PacketParsable<Entry> for AnyAddressFamilyEntriesParser {
+    fn parse_entry<'a>(
+        &'a self,
+        cursor: usize,
+        bytes: &'a [u8],
+    ) -> Result<(Entry, usize), ParseError> {
+        parse_entry(cursor, bytes, true)
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use crate::metric::Metric;
     use crate::parser::ParseError::NotZeroByte;
     use crate::v1::{EntriesParser, Entry};
     use crate::{address_family, parser};
@@ -98,7 +182,7 @@ mod tests {
             .as_slice(),
         );
 
-        assert_eq!(result.is_ok(), true);
+        assert!(result.is_ok());
 
         let entries = result.unwrap();
         assert_eq!(
@@ -106,7 +190,7 @@ mod tests {
             vec![Entry {
                 address_family_identifier: address_family::Identifier::IP,
                 ip_address: Ipv4Addr::new(192, 0, 2, 100),
-                metric: 67305985,
+                metric: Metric::from_raw(67305985),
             }]
         );
     }
@@ -138,7 +222,7 @@ mod tests {
             .as_slice(),
         );
 
-        assert_eq!(result.is_ok(), true);
+        assert!(result.is_ok());
 
         let entries = result.unwrap();
         assert_eq!(
@@ -147,17 +231,17 @@ mod tests {
                 Entry {
                     address_family_identifier: address_family::Identifier::IP,
                     ip_address: Ipv4Addr::new(192, 0, 2, 100),
-                    metric: 67305985,
+                    metric: Metric::from_raw(67305985),
                 },
                 Entry {
                     address_family_identifier: address_family::Identifier::IP,
                     ip_address: Ipv4Addr::new(192, 0, 2, 101),
-                    metric: 1,
+                    metric: Metric::from_raw(1),
                 },
                 Entry {
                     address_family_identifier: address_family::Identifier::IP,
                     ip_address: Ipv4Addr::new(192, 0, 2, 102),
-                    metric: 2,
+                    metric: Metric::from_raw(2),
                 },
             ]
         );
@@ -179,7 +263,7 @@ mod tests {
             ]
             .as_slice(),
         );
-        assert_eq!(result.unwrap_err(), NotZeroByte(1, 7));
+        assert_eq!(result.unwrap_err(), NotZeroByte(1, 6));
 
         let result = parser::parse_entries(
             &parser,
@@ -194,7 +278,7 @@ mod tests {
             ]
             .as_slice(),
         );
-        assert_eq!(result.unwrap_err(), NotZeroByte(1, 8));
+        assert_eq!(result.unwrap_err(), NotZeroByte(1, 7));
 
         let result = parser::parse_entries(
             &parser,
@@ -209,7 +293,7 @@ mod tests {
             ]
             .as_slice(),
         );
-        assert_eq!(result.unwrap_err(), NotZeroByte(1, 13));
+        assert_eq!(result.unwrap_err(), NotZeroByte(1, 12));
 
         let result = parser::parse_entries(
             &parser,
@@ -224,7 +308,7 @@ mod tests {
             ]
             .as_slice(),
         );
-        assert_eq!(result.unwrap_err(), NotZeroByte(1, 14));
+        assert_eq!(result.unwrap_err(), NotZeroByte(1, 13));
 
         let result = parser::parse_entries(
             &parser,
@@ -239,7 +323,7 @@ mod tests {
             ]
             .as_slice(),
         );
-        assert_eq!(result.unwrap_err(), NotZeroByte(1, 15));
+        assert_eq!(result.unwrap_err(), NotZeroByte(1, 14));
 
         let result = parser::parse_entries(
             &parser,
@@ -254,7 +338,7 @@ mod tests {
             ]
             .as_slice(),
         );
-        assert_eq!(result.unwrap_err(), NotZeroByte(1, 16));
+        assert_eq!(result.unwrap_err(), NotZeroByte(1, 15));
 
         let result = parser::parse_entries(
             &parser,
@@ -269,7 +353,7 @@ mod tests {
             ]
             .as_slice(),
         );
-        assert_eq!(result.unwrap_err(), NotZeroByte(1, 17));
+        assert_eq!(result.unwrap_err(), NotZeroByte(1, 16));
 
         let result = parser::parse_entries(
             &parser,
@@ -284,7 +368,7 @@ mod tests {
             ]
             .as_slice(),
         );
-        assert_eq!(result.unwrap_err(), NotZeroByte(1, 18));
+        assert_eq!(result.unwrap_err(), NotZeroByte(1, 17));
 
         let result = parser::parse_entries(
             &parser,
@@ -299,7 +383,7 @@ mod tests {
             ]
             .as_slice(),
         );
-        assert_eq!(result.unwrap_err(), NotZeroByte(1, 19));
+        assert_eq!(result.unwrap_err(), NotZeroByte(1, 18));
 
         let result = parser::parse_entries(
             &parser,
@@ -314,6 +398,6 @@ mod tests {
             ]
             .as_slice(),
         );
-        assert_eq!(result.unwrap_err(), NotZeroByte(1, 20));
+        assert_eq!(result.unwrap_err(), NotZeroByte(1, 19));
     }
 }
@@ -1,10 +1,22 @@
+use crate::entry::RouteEntry;
 use crate::metric::Metric;
+use crate::parser::{PacketParsable, Parsable, ParseError, Parsed};
+use crate::prefix::{Ipv4Prefix, PrefixError};
 use crate::route_tag::RouteTag;
-use crate::serializer::{Serializable, SerializeError};
-use crate::{address_family, ipv4, metric, parser::PacketParsable, parser::ParseError, route_tag};
+use crate::serializer::{check_buffer_length, Serializable, SerializeError};
+use crate::{address_family, ipv4, metric, route_tag};
+use std::fmt;
 use std::net::Ipv4Addr;
+use thiserror::Error;
 
-#[derive(PartialEq, Debug)]
+#[derive(Error, Debug, PartialEq, Clone)]
+pub enum EntryError {
+    #[error("metric {0} is out of the range 1..=16")]
+    MetricOutOfRange(Metric),
+}
+
+#[derive(PartialEq, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Entry {
     address_family_identifier: address_family::Identifier,
     route_tag: RouteTag,
@@ -56,22 +68,175 @@ impl Entry {
     pub fn get_metric(&self) -> Metric {
         self.metric
     }
+
+    /// builder returns a builder of the route entry for the prefix.
+    pub fn builder(prefix: Ipv4Prefix) -> EntryBuilder {
+        EntryBuilder::new(prefix)
+    }
+
+    /// get_prefix returns the prefix that the IP address and the subnet mask represent.
+    /// It fails when the subnet mask is not contiguous or the IP address has the host bits.
+    pub fn get_prefix(&self) -> Result<Ipv4Prefix, PrefixError> {
+        Ipv4Prefix::from_address_and_mask(self.ip_address, self.subnet_mask)
+    }
+}
+
+/// The subnet mask, the next hop and the route tag of RIPv2 are always present, even if they are zero.
+impl RouteEntry for Entry {
+    fn address_family_identifier(&self) -> address_family::Identifier {
+        self.get_address_family_identifier()
+    }
+
+    fn destination(&self) -> Ipv4Addr {
+        self.get_ip_address()
+    }
+
+    fn subnet_mask(&self) -> Option<Ipv4Addr> {
+        Some(self.get_subnet_mask())
+    }
+
+    fn next_hop(&self) -> Option<Ipv4Addr> {
+        Some(self.get_next_hop())
+    }
+
+    fn route_tag(&self) -> Option<RouteTag> {
+        Some(self.get_route_tag())
+    }
+
+    fn metric(&self) -> Metric {
+        self.get_metric()
+    }
+}
+
+/// The entry is formatted as `10.0.0.0/8 via 0.0.0.0 metric 2 tag 100`. The destination falls back to
+/// `192.0.2.1 mask 255.255.0.0` when the address and the subnet mask don't make up a canonical prefix, and
+/// the address family identifier follows only when it is not IP.
+impl fmt::Display for Entry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.get_prefix() {
+            Ok(prefix) => write!(f, "{}", prefix)?,
+            Err(_) => write!(f, "{} mask {}", self.ip_address, self.subnet_mask)?,
+        }
+        write!(
+            f,
+            " via {} metric {} tag {}",
+            self.next_hop, self.metric, self.route_tag
+        )?;
+        if self.address_family_identifier != address_family::Identifier::IP {
+            write!(f, " afi {}", self.address_family_identifier.to_u16())?;
+        }
+        Ok(())
+    }
+}
+
+/// EntryBuilder builds a validated route entry from a prefix.
+/// The next hop defaults to 0.0.0.0 (i.e. via the originator of the packet), the route tag to 0 and
+/// the metric to 1.
+#[derive(PartialEq, Debug)]
+pub struct EntryBuilder {
+    prefix: Ipv4Prefix,
+    route_tag: RouteTag,
+    next_hop: Ipv4Addr,
+    metric: Metric,
+}
+
+impl EntryBuilder {
+    pub fn new(prefix: Ipv4Prefix) -> Self {
+        EntryBuilder {
+            prefix,
+            route_tag: RouteTag::default(),
+            next_hop: Ipv4Addr::UNSPECIFIED,
+            metric: Metric::from_raw(1),
+        }
+    }
+
+    pub fn route_tag(mut self, route_tag: RouteTag) -> Self {
+        self.route_tag = route_tag;
+        self
+    }
+
+    pub fn next_hop(mut self, next_hop: Ipv4Addr) -> Self {
+        self.next_hop = next_hop;
+        self
+    }
+
+    pub fn metric(mut self, metric: Metric) -> Self {
+        self.metric = metric;
+        self
+    }
+
+    pub fn build(self) -> Result<Entry, EntryError> {
+        if !self.metric.is_valid() {
+            return Err(EntryError::MetricOutOfRange(self.metric));
+        }
+
+        Ok(Entry::new(
+            address_family::Identifier::IP,
+            self.route_tag,
+            self.prefix.get_address(),
+            self.prefix.get_subnet_mask(),
+            self.next_hop,
+            self.metric,
+        ))
+    }
 }
 
 impl Serializable for Entry {
-    fn to_bytes(&self) -> Result<Vec<u8>, SerializeError> {
-        Ok([
-            self.get_address_family_identifier().to_bytes()?,
-            route_tag::to_bytes(self.get_route_tag())?,
-            ipv4::to_bytes(self.get_ip_address())?,
-            ipv4::to_bytes(self.get_subnet_mask())?,
-            ipv4::to_bytes(self.get_next_hop())?,
-            metric::to_bytes(self.get_metric())?,
-        ]
-        .concat())
+    fn serialized_len(&self) -> usize {
+        20
+    }
+
+    fn write_bytes(&self, buf: &mut [u8]) -> Result<usize, SerializeError> {
+        check_buffer_length(buf, self.serialized_len())?;
+
+        let mut cursor = self.get_address_family_identifier().write_bytes(buf)?;
+        cursor += route_tag::write_bytes(self.get_route_tag(), &mut buf[cursor..])?;
+        cursor += ipv4::write_bytes(self.get_ip_address(), &mut buf[cursor..])?;
+        cursor += ipv4::write_bytes(self.get_subnet_mask(), &mut buf[cursor..])?;
+        cursor += ipv4::write_bytes(self.get_next_hop(), &mut buf[cursor..])?;
+        cursor += metric::write_bytes(self.get_metric(), &mut buf[cursor..])?;
+
+        Ok(cursor)
+    }
+}
+
+impl Parsable for Entry {
+    fn parse(cursor: usize, bytes: &[u8]) -> Result<Parsed<Entry>, ParseError> {
+        parse_entry(cursor, bytes, false)
     }
 }
 
+/// parse_entry parses an entry; the unknown address family identifier is an error unless
+/// `allows_unknown_address_family` is true.
+fn parse_entry(
+    cursor: usize,
+    bytes: &[u8],
+    allows_unknown_address_family: bool,
+) -> Result<Parsed<Entry>, ParseError> {
+    let (address_family_identifier, cursor) = if allows_unknown_address_family {
+        address_family::parse_any(cursor, bytes)?
+    } else {
+        address_family::Identifier::parse(cursor, bytes)?
+    };
+    let (route_tag, cursor) = route_tag::parse(cursor, bytes)?;
+    let (ip_address, cursor) = ipv4::parse(cursor, bytes)?;
+    let (subnet_mask, cursor) = ipv4::parse(cursor, bytes)?;
+    let (next_hop, cursor) = ipv4::parse(cursor, bytes)?;
+    let (metric, cursor) = metric::parse(cursor, bytes)?;
+
+    Ok((
+        Entry::new(
+            address_family_identifier,
+            route_tag,
+            ip_address,
+            subnet_mask,
+            next_hop,
+            metric,
+        ),
+        cursor,
+    ))
+}
+
 pub struct EntriesParser {}
 
 impl PacketParsable<Entry> for EntriesParser {
@@ -80,33 +245,140 @@ impl PacketParsable<Entry> for EntriesParser {
         cursor: usize,
         bytes: &'a [u8],
     ) -> Result<(Entry, usize), ParseError> {
-        let (address_family_identifier, cursor) = address_family::Identifier::parse(cursor, bytes)?;
-        let (route_tag, cursor) = route_tag::parse(cursor, bytes)?;
-        let (ip_address, cursor) = ipv4::parse(cursor, bytes)?;
-        let (subnet_mask, cursor) = ipv4::parse(cursor, bytes)?;
-        let (next_hop, cursor) = ipv4::parse(cursor, bytes)?;
-        let (metric, cursor) = metric::parse(cursor, bytes)?;
-
-        Ok((
-            Entry::new(
-                address_family_identifier,
-                route_tag,
-                ip_address,
-                subnet_mask,
-                next_hop,
-                metric,
-            ),
-            cursor,
-        ))
+        Entry::parse(cursor, bytes)
+    }
+}
+
+/// AnyAddressFamilyEntriesParser parses the entries as well as [`EntriesParser`],
+/// but it accepts the unknown address family identifiers.
+pub(crate) struct AnyAddressFamilyEntriesParser {}
+
+impl PacketParsable<Entry> for AnyAddressFamilyEntriesParser {
+    fn parse_entry<'a>(
+        &'a self,
+        cursor: usize,
+        bytes: &'a [u8],
+    ) -> Result<(Entry, usize), ParseError> {
+        parse_entry(cursor, bytes, true)
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::v2::{EntriesParser, Entry};
-    use crate::{address_family, parser};
+    use crate::header::Header;
+    use crate::metric::Metric;
+    use crate::parser::Parsable;
+    use crate::prefix::{Ipv4Prefix, PrefixError};
+    use crate::route_tag::RouteTag;
+    use crate::serializer::Serializable;
+    use crate::v2::{EntriesParser, Entry, EntryError};
+    use crate::{address_family, command, parser, version};
     use std::net::Ipv4Addr;
 
+    #[test]
+    fn test_builder() {
+        let entry = Entry::builder("192.0.2.0/24".parse().unwrap())
+            .build()
+            .unwrap();
+        assert_eq!(
+            entry,
+            Entry::new(
+                address_family::Identifier::IP,
+                RouteTag::new(0),
+                Ipv4Addr::new(192, 0, 2, 0),
+                Ipv4Addr::new(255, 255, 255, 0),
+                Ipv4Addr::new(0, 0, 0, 0),
+                Metric::from_raw(1),
+            )
+        );
+
+        let entry = Entry::builder("10.0.0.0/8".parse().unwrap())
+            .next_hop(Ipv4Addr::new(192, 0, 2, 1))
+            .route_tag(RouteTag::new(100))
+            .metric(Metric::from_raw(16))
+            .build()
+            .unwrap();
+        assert_eq!(
+            entry,
+            Entry::new(
+                address_family::Identifier::IP,
+                RouteTag::new(100),
+                Ipv4Addr::new(10, 0, 0, 0),
+                Ipv4Addr::new(255, 0, 0, 0),
+                Ipv4Addr::new(192, 0, 2, 1),
+                Metric::from_raw(16),
+            )
+        );
+        assert_eq!(
+            entry.get_prefix().unwrap(),
+            "10.0.0.0/8".parse::<Ipv4Prefix>().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_builder_rejects_out_of_range_metric() {
+        let prefix: Ipv4Prefix = "192.0.2.0/24".parse().unwrap();
+        assert_eq!(
+            Entry::builder(prefix)
+                .metric(Metric::from_raw(0))
+                .build()
+                .unwrap_err(),
+            EntryError::MetricOutOfRange(Metric::from_raw(0))
+        );
+        assert_eq!(
+            Entry::builder(prefix)
+                .metric(Metric::from_raw(17))
+                .build()
+                .unwrap_err(),
+            EntryError::MetricOutOfRange(Metric::from_raw(17))
+        );
+    }
+
+    #[test]
+    fn test_get_prefix_of_non_canonical_entry() {
+        let entry = Entry::new(
+            address_family::Identifier::IP,
+            RouteTag::new(0),
+            Ipv4Addr::new(192, 0, 2, 1),
+            Ipv4Addr::new(255, 255, 255, 0),
+            Ipv4Addr::new(0, 0, 0, 0),
+            Metric::from_raw(1),
+        );
+        assert_eq!(
+            entry.get_prefix().unwrap_err(),
+            PrefixError::NonCanonicalPrefix(Ipv4Addr::new(192, 0, 2, 1), 24)
+        );
+    }
+
+    #[test]
+    fn test_encode_and_decode_custom_layout() {
+        let header = Header::new(command::Kind::Response, version::Version::Version2);
+        let entry = Entry::new(
+            address_family::Identifier::IP,
+            RouteTag::new(258),
+            Ipv4Addr::new(192, 0, 2, 100),
+            Ipv4Addr::new(255, 255, 255, 0),
+            Ipv4Addr::new(192, 0, 2, 111),
+            Metric::from_raw(1),
+        );
+
+        let mut buf = [0; 28];
+        let mut cursor = header.write_bytes(&mut buf).unwrap();
+        cursor += entry.write_bytes(&mut buf[cursor..]).unwrap();
+        cursor += Metric::from_raw(0xdeadbeef)
+            .write_bytes(&mut buf[cursor..])
+            .unwrap();
+        assert_eq!(cursor, 28);
+
+        let (decoded_header, cursor) = Header::parse(0, &buf).unwrap();
+        assert_eq!(decoded_header, header);
+        let (decoded_entry, cursor) = Entry::parse(cursor, &buf).unwrap();
+        assert_eq!(decoded_entry, entry);
+        let (trailer, cursor) = Metric::parse(cursor, &buf).unwrap();
+        assert_eq!(trailer.to_u32(), 0xdeadbeef);
+        assert_eq!(cursor, 28);
+    }
+
     #[test]
     fn test_parse_packet_for_single_entry() {
         let parser = EntriesParser {};
@@ -124,18 +396,18 @@ mod tests {
             .as_slice(),
         );
 
-        assert_eq!(result.is_ok(), true);
+        assert!(result.is_ok());
 
         let entries = result.unwrap();
         assert_eq!(
             entries,
             vec![Entry {
                 address_family_identifier: address_family::Identifier::IP,
-                route_tag: 258,
+                route_tag: RouteTag::new(258),
                 ip_address: Ipv4Addr::new(192, 0, 2, 100),
                 subnet_mask: Ipv4Addr::new(255, 255, 255, 0),
                 next_hop: Ipv4Addr::new(192, 0, 2, 111),
-                metric: 67305985,
+                metric: Metric::from_raw(67305985),
             }]
         );
     }
@@ -167,7 +439,7 @@ mod tests {
             .as_slice(),
         );
 
-        assert_eq!(result.is_ok(), true);
+        assert!(result.is_ok());
 
         let entries = result.unwrap();
         assert_eq!(
@@ -175,27 +447,27 @@ mod tests {
             vec![
                 Entry {
                     address_family_identifier: address_family::Identifier::IP,
-                    route_tag: 258,
+                    route_tag: RouteTag::new(258),
                     ip_address: Ipv4Addr::new(192, 0, 2, 100),
                     subnet_mask: Ipv4Addr::new(255, 255, 255, 0),
                     next_hop: Ipv4Addr::new(192, 0, 2, 200),
-                    metric: 67305985,
+                    metric: Metric::from_raw(67305985),
                 },
                 Entry {
                     address_family_identifier: address_family::Identifier::IP,
-                    route_tag: 1,
+                    route_tag: RouteTag::new(1),
                     ip_address: Ipv4Addr::new(192, 0, 2, 101),
                     subnet_mask: Ipv4Addr::new(255, 255, 255, 0),
                     next_hop: Ipv4Addr::new(192, 0, 2, 201),
-                    metric: 1,
+                    metric: Metric::from_raw(1),
                 },
                 Entry {
                     address_family_identifier: address_family::Identifier::IP,
-                    route_tag: 2,
+                    route_tag: RouteTag::new(2),
                     ip_address: Ipv4Addr::new(192, 0, 2, 102),
                     subnet_mask: Ipv4Addr::new(255, 255, 255, 0),
                     next_hop: Ipv4Addr::new(192, 0, 2, 202),
-                    metric: 2,
+                    metric: Metric::from_raw(2),
                 },
             ]
         );
@@ -1,12 +1,23 @@
-use crate::serializer::SerializeError::UnknownVersion;
-use crate::serializer::{Serializable, SerializeError};
+use crate::byte_reader;
+use crate::parser::{Parsable, ParseError, Parsed};
+use crate::serializer::{write_slice, Serializable, SerializeError};
+use std::fmt;
+use std::str::FromStr;
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq, Clone)]
+pub enum VersionError {
+    #[error("invalid version notation \"{0}\"")]
+    InvalidNotation(String),
+}
 
 #[derive(PartialEq, Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Version {
     MustBeDiscarded, // RFC1058
     Version1,        // RFC1058
     Version2,        // RFC2453
-    Unknown,
+    Unknown(u8),
 }
 
 impl Version {
@@ -15,42 +26,105 @@ impl Version {
             0 => Version::MustBeDiscarded,
             1 => Version::Version1,
             2 => Version::Version2,
-            _ => Version::Unknown,
+            _ => Version::Unknown(value),
         }
     }
 
-    pub fn to_u8(&self) -> Option<u8> {
+    pub fn to_u8(&self) -> u8 {
         match self {
-            Version::MustBeDiscarded => Some(0),
-            Version::Version1 => Some(1),
-            Version::Version2 => Some(2),
-            Version::Unknown => None,
+            Version::MustBeDiscarded => 0,
+            Version::Version1 => 1,
+            Version::Version2 => 2,
+            Version::Unknown(value) => *value,
         }
     }
 }
 
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "v{}", self.to_u8())
+    }
+}
+
+impl FromStr for Version {
+    type Err = VersionError;
+
+    /// from_str accepts the notation that Display gives, e.g. `v2`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.strip_prefix('v')
+            .and_then(|value| u8::from_str(value).ok())
+            .map(Version::from_u8)
+            .ok_or_else(|| VersionError::InvalidNotation(s.to_string()))
+    }
+}
+
+impl Parsable for Version {
+    fn parse(cursor: usize, bytes: &[u8]) -> Result<Parsed<Version>, ParseError> {
+        let (version_byte, cursor) = byte_reader::read(cursor, bytes)?;
+        Ok((Version::from_u8(version_byte), cursor))
+    }
+}
+
 impl Serializable for Version {
-    fn to_bytes(&self) -> Result<Vec<u8>, SerializeError> {
-        match self.to_u8() {
-            Some(byte) => Ok(vec![byte]),
-            None => Err(UnknownVersion),
-        }
+    fn serialized_len(&self) -> usize {
+        1
+    }
+
+    fn write_bytes(&self, buf: &mut [u8]) -> Result<usize, SerializeError> {
+        write_slice(&[self.to_u8()], buf)
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use crate::parser::{Parsable, ParseError};
     use crate::serializer::Serializable;
-    use crate::serializer::SerializeError::UnknownVersion;
     use crate::version;
     use crate::version::Version::{MustBeDiscarded, Unknown, Version1, Version2};
+    use crate::version::VersionError;
+    use std::str::FromStr;
 
     #[test]
     fn test_from_u8() {
         assert_eq!(version::Version::from_u8(0), MustBeDiscarded);
         assert_eq!(version::Version::from_u8(1), Version1);
         assert_eq!(version::Version::from_u8(2), Version2);
-        assert_eq!(version::Version::from_u8(3), Unknown);
+        assert_eq!(version::Version::from_u8(3), Unknown(3));
+    }
+
+    #[test]
+    fn test_parse() {
+        let (v, cursor) = version::Version::parse(1, vec![0x02, 0x01].as_slice()).unwrap();
+        assert_eq!(v, Version1);
+        assert_eq!(cursor, 2);
+        let (v, cursor) = version::Version::parse(0, vec![0x07].as_slice()).unwrap();
+        assert_eq!(v, Unknown(7));
+        assert_eq!(cursor, 1);
+
+        assert_eq!(
+            version::Version::parse(1, vec![0x02].as_slice()).unwrap_err(),
+            ParseError::InsufficientInputBytesLength(1)
+        );
+    }
+
+    #[test]
+    fn test_from_u8_and_to_u8_round_trip() {
+        for value in 0..=u8::MAX {
+            assert_eq!(version::Version::from_u8(value).to_u8(), value);
+        }
+    }
+
+    #[test]
+    fn test_from_str() {
+        assert_eq!(version::Version::from_str("v1"), Ok(Version1));
+        assert_eq!(version::Version::from_str("v2"), Ok(Version2));
+        assert_eq!(version::Version::from_str("v3"), Ok(Unknown(3)));
+        for notation in ["2", "v", "v256", "version2"] {
+            assert_eq!(
+                version::Version::from_str(notation),
+                Err(VersionError::InvalidNotation(notation.to_string()))
+            );
+        }
     }
 
     #[test]
@@ -58,6 +132,6 @@ mod tests {
         assert_eq!(MustBeDiscarded.to_bytes().unwrap(), vec![0x00]);
         assert_eq!(Version1.to_bytes().unwrap(), vec![0x01]);
         assert_eq!(Version2.to_bytes().unwrap(), vec![0x02]);
-        assert_eq!(Unknown.to_bytes().unwrap_err(), UnknownVersion);
+        assert_eq!(Unknown(3).to_bytes().unwrap(), vec![0x03]);
     }
 }
@@ -0,0 +1,275 @@
+use crate::metric::Metric;
+use crate::packet::{ENTRY_LENGTH, HEADER_LENGTH};
+use crate::parser::{check_length, walk_entries, ParseError, ParseOptions};
+use crate::route_tag::RouteTag;
+use crate::{address_family, command, header, v1, v2, version};
+use std::net::Ipv4Addr;
+
+/// PacketView is a borrowed view of a RIP packet.
+/// The given bytes are validated once on construction, and then each field is decoded on demand
+/// without copying the bytes.
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub struct PacketView<'a> {
+    bytes: &'a [u8],
+    version: version::Version,
+}
+
+impl<'a> PacketView<'a> {
+    pub fn new(bytes: &'a [u8]) -> Result<Self, ParseError> {
+        let (header, cursor) = header::parse(0, bytes)?;
+        check_length(bytes, &ParseOptions::default())?;
+
+        match header.get_version() {
+            version::Version::Version1 => {
+                walk_entries(
+                    &v1::EntriesParser {},
+                    cursor,
+                    bytes,
+                    header.get_command().allows_empty_entries(),
+                    |_| {},
+                )?;
+            }
+            version::Version::Version2 => {
+                walk_entries(
+                    &v2::EntriesParser {},
+                    cursor,
+                    bytes,
+                    header.get_command().allows_empty_entries(),
+                    |_| {},
+                )?;
+            }
+            version::Version::MustBeDiscarded => {
+                return Err(ParseError::MustBeDiscardedVersion(1));
+            }
+            version::Version::Unknown(_) => {
+                return Err(ParseError::UnknownVersion(1));
+            }
+        };
+
+        Ok(PacketView {
+            bytes,
+            version: header.get_version(),
+        })
+    }
+
+    pub fn get_header(&self) -> header::Header {
+        header::Header::new(self.get_command(), self.get_version())
+    }
+
+    pub fn get_command(&self) -> command::Kind {
+        command::Kind::from_u8(self.bytes[0])
+    }
+
+    pub fn get_version(&self) -> version::Version {
+        self.version
+    }
+
+    pub fn get_entries(&self) -> EntryViews<'a> {
+        EntryViews {
+            chunks: self.bytes[HEADER_LENGTH..].chunks_exact(ENTRY_LENGTH),
+        }
+    }
+
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.bytes
+    }
+}
+
+/// EntryView is a borrowed view of a RIP entry in a validated [`PacketView`].
+/// The accessors follow the RIPv2 layout; for a RIPv1 entry the route tag, the subnet mask and
+/// the next hop come from the must-be-zero fields, so they are always zero.
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub struct EntryView<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> EntryView<'a> {
+    pub fn get_address_family_identifier(&self) -> address_family::Identifier {
+        address_family::Identifier::from_u16(read_u16(self.bytes, 0))
+    }
+
+    pub fn get_route_tag(&self) -> RouteTag {
+        RouteTag::new(read_u16(self.bytes, 2))
+    }
+
+    pub fn get_ip_address(&self) -> Ipv4Addr {
+        Ipv4Addr::from(read_u32(self.bytes, 4))
+    }
+
+    pub fn get_subnet_mask(&self) -> Ipv4Addr {
+        Ipv4Addr::from(read_u32(self.bytes, 8))
+    }
+
+    pub fn get_next_hop(&self) -> Ipv4Addr {
+        Ipv4Addr::from(read_u32(self.bytes, 12))
+    }
+
+    pub fn get_metric(&self) -> Metric {
+        Metric::from_raw(read_u32(self.bytes, 16))
+    }
+
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.bytes
+    }
+}
+
+/// EntryViews is an iterator that walks the entries of a [`PacketView`] lazily.
+#[derive(Clone, Debug)]
+pub struct EntryViews<'a> {
+    chunks: std::slice::ChunksExact<'a, u8>,
+}
+
+impl<'a> Iterator for EntryViews<'a> {
+    type Item = EntryView<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.chunks.next().map(|bytes| EntryView { bytes })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.chunks.size_hint()
+    }
+}
+
+impl ExactSizeIterator for EntryViews<'_> {}
+
+fn read_u16(bytes: &[u8], offset: usize) -> u16 {
+    u16::from_be_bytes([bytes[offset], bytes[offset + 1]])
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> u32 {
+    u32::from_be_bytes([
+        bytes[offset],
+        bytes[offset + 1],
+        bytes[offset + 2],
+        bytes[offset + 3],
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::ParseError;
+    use crate::view::PacketView;
+    use crate::{address_family, command, header::Header, version};
+    use std::net::Ipv4Addr;
+
+    #[test]
+    fn test_v1_packet_view() {
+        let bytes = vec![
+            2, 1, 0, 0, //
+            0, 2, 0, 0, //
+            192, 0, 2, 100, //
+            0, 0, 0, 0, //
+            0, 0, 0, 0, //
+            4, 3, 2, 1, //
+            0, 2, 0, 0, //
+            192, 0, 2, 101, //
+            0, 0, 0, 0, //
+            0, 0, 0, 0, //
+            0, 0, 0, 1, //
+        ];
+        let view = PacketView::new(bytes.as_slice()).unwrap();
+
+        assert_eq!(
+            view.get_header(),
+            Header::new(command::Kind::Response, version::Version::Version1)
+        );
+        assert_eq!(view.as_bytes(), bytes.as_slice());
+
+        let mut entries = view.get_entries();
+        assert_eq!(entries.len(), 2);
+
+        let entry = entries.next().unwrap();
+        assert_eq!(
+            entry.get_address_family_identifier(),
+            address_family::Identifier::IP
+        );
+        assert_eq!(entry.get_route_tag().to_u16(), 0);
+        assert_eq!(entry.get_ip_address(), Ipv4Addr::new(192, 0, 2, 100));
+        assert_eq!(entry.get_subnet_mask(), Ipv4Addr::new(0, 0, 0, 0));
+        assert_eq!(entry.get_next_hop(), Ipv4Addr::new(0, 0, 0, 0));
+        assert_eq!(entry.get_metric().to_u32(), 67305985);
+        assert_eq!(entry.as_bytes(), &bytes[4..24]);
+
+        let entry = entries.next().unwrap();
+        assert_eq!(entry.get_ip_address(), Ipv4Addr::new(192, 0, 2, 101));
+        assert_eq!(entry.get_metric().to_u32(), 1);
+
+        assert_eq!(entries.next(), None);
+    }
+
+    #[test]
+    fn test_v2_packet_view() {
+        let bytes = vec![
+            2, 2, 0, 0, //
+            0, 2, 1, 2, //
+            192, 0, 2, 100, //
+            255, 255, 255, 0, //
+            192, 0, 2, 111, //
+            4, 3, 2, 1, //
+        ];
+        let view = PacketView::new(bytes.as_slice()).unwrap();
+
+        assert_eq!(view.get_command(), command::Kind::Response);
+        assert_eq!(view.get_version(), version::Version::Version2);
+
+        let entries: Vec<_> = view.get_entries().collect();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(
+            entries[0].get_address_family_identifier(),
+            address_family::Identifier::IP
+        );
+        assert_eq!(entries[0].get_route_tag().to_u16(), 258);
+        assert_eq!(entries[0].get_ip_address(), Ipv4Addr::new(192, 0, 2, 100));
+        assert_eq!(
+            entries[0].get_subnet_mask(),
+            Ipv4Addr::new(255, 255, 255, 0)
+        );
+        assert_eq!(entries[0].get_next_hop(), Ipv4Addr::new(192, 0, 2, 111));
+        assert_eq!(entries[0].get_metric().to_u32(), 67305985);
+    }
+
+    #[test]
+    fn test_packet_view_validates_bytes() {
+        assert_eq!(
+            PacketView::new(
+                vec![
+                    2, 1, 0, 0, //
+                    0, 2, 1, 0, // the third byte is not zero
+                    192, 0, 2, 100, //
+                    0, 0, 0, 0, //
+                    0, 0, 0, 0, //
+                    4, 3, 2, 1, //
+                ]
+                .as_slice()
+            )
+            .unwrap_err(),
+            ParseError::NotZeroByte(1, 6)
+        );
+
+        assert_eq!(
+            PacketView::new(
+                vec![
+                    2, 2, 0, 0, //
+                    0, 2, 0, 0, //
+                    192, 0, 2, 100, //
+                    255, 255, 255, 0, //
+                    0, 0, 0, 0, //
+                    4, 3, 2, // missing a trailing byte
+                ]
+                .as_slice()
+            )
+            .unwrap_err(),
+            ParseError::TrailingBytes(19, 4)
+        );
+
+        assert_eq!(
+            PacketView::new(vec![2, 0, 0, 0].as_slice()).unwrap_err(),
+            ParseError::MustBeDiscardedVersion(1)
+        );
+        assert_eq!(
+            PacketView::new(vec![0, 2, 0, 0].as_slice()).unwrap_err(),
+            ParseError::EmptyRIPEntry(4)
+        );
+    }
+}
@@ -8,7 +8,7 @@ pub(crate) fn skip(
     for _ in 0..num_of_zero_bytes {
         let (byte, new_cursor) = byte_reader::read(cursor, bytes)?;
         if byte != 0 {
-            return Err(ParseError::NotZeroByte(byte, new_cursor));
+            return Err(ParseError::NotZeroByte(byte, cursor));
         }
         cursor = new_cursor;
     }